@@ -1,74 +1,62 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use ::omni_arsenal::bubblesort;
-use ::omni_arsenal::insertionsort;
-use ::omni_arsenal::selectionsort;
-use ::omni_arsenal::mergesort;
-use ::omni_arsenal::quicksort;
-use ::omni_arsenal::heapsort;
-
-
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion};
+use ::omni_arsenal::all_integer_sorters;
+use ::omni_arsenal::all_sorters;
+use ::omni_arsenal::inputs::{self, Pattern};
+use ::omni_arsenal::HeapSort;
+use ::omni_arsenal::{ParallelMergeSort, ParallelQuickSort};
+use ::omni_arsenal::Sorter;
+
+
+// every sorter on a clone of the same input
+fn bench_input<T: Clone>(group: &mut BenchmarkGroup<WallTime>, sorters: &[Box<dyn Sorter<T>>], size: usize, arr: &[T]) {
+    for sorter in sorters.iter() {
+        group.bench_function(format!("{} {}", sorter.name(), size), |b| b.iter(|| {
+            let mut arr_clone = arr.to_vec();
+            sorter.sort(&mut arr_clone);
+        }));
+    }
+}
 
 fn benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("Sorting algorithms");
+    let sorters = all_integer_sorters::<i32>();
+
+    for pattern in Pattern::ALL {
+        let mut group = c.benchmark_group(format!("Sorting algorithms {}", pattern.name()));
+        for &size in [100, 1000, 10000].iter() {
+            bench_input(&mut group, &sorters, size, &pattern.generate(size));
+        }
+        group.finish();
+    }
+}
 
+// comparisons that cost more than an integer compare, and elements that cost more to move
+fn benchmark_types(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sorting algorithms strings");
     for &size in [100, 1000, 10000].iter() {
-        let mut rng = thread_rng();
-        let mut arr: Vec<_> = (0..size).collect();
-        arr.shuffle(&mut rng);
-
-        group.bench_function(format!("Bubble sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            bubblesort(&mut arr_clone);
-        }));
-
-        group.bench_function(format!("Insertion sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            insertionsort(&mut arr_clone);
-        }));
-
-        group.bench_function(format!("Selection sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            selectionsort(&mut arr_clone);
-        }));
-
-        group.bench_function(format!("Merge sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            mergesort(&mut arr_clone);
-        }));
-
-        group.bench_function(format!("Quick sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            quicksort(&mut arr_clone);
-        }));
-
-        group.bench_function(format!("Heap sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            heapsort(&mut arr_clone);
-        }));
+        bench_input(&mut group, &all_sorters::<String>(), size, &inputs::random_strings(size, 16));
+    }
+    group.finish();
 
+    let mut group = c.benchmark_group("Sorting algorithms large structs");
+    for &size in [100, 1000, 10000].iter() {
+        bench_input(&mut group, &all_sorters::<inputs::LargeStruct>(), size, &inputs::large_structs(size));
     }
     group.finish();
 }
 
-//make a heavy test on heap sort 
+//make a heavy test on heap sort and the parallel sorts, same input for all
 fn benchmark_heavy(c: &mut Criterion) {
     let mut group = c.benchmark_group("Sorting algorithms");
-
-    for &size in [1000000].iter() {
-        let mut rng = thread_rng();
-        let mut arr: Vec<_> = (0..size).collect();
-        arr.shuffle(&mut rng);
-
-        group.bench_function(format!("Heap sort {}", size), |b| b.iter(|| {
-            let mut arr_clone = arr.clone();
-            heapsort(&mut arr_clone);
-        }));
-
-    }
+    let sorters: Vec<Box<dyn Sorter<i32>>> = vec![
+        Box::new(HeapSort),
+        Box::new(ParallelMergeSort),
+        Box::new(ParallelQuickSort),
+    ];
+
+    let size = 1000000;
+    bench_input(&mut group, &sorters, size, &inputs::random(size));
     group.finish();
 }
 
-criterion_group!(benches, benchmark, benchmark_heavy);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, benchmark, benchmark_types, benchmark_heavy);
+criterion_main!(benches);
@@ -1,21 +1,49 @@
-use std::ops::Index;
-pub fn bubblesort<T, O>(container: &mut T)
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+pub fn bubblesort<T: PartialOrd>(arr: &mut [T]) {
+    bubblesort_by(arr, partial_order);
+}
+
+pub fn bubblesort_by<T, F>(arr: &mut [T], compare: F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized,
-    O: PartialOrd,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    let len = container.as_ref().len();
+    let mut compare = counted(compare);
+    let len = arr.len();
 
     for i in 0..len {
         for j in 0..len - i - 1 {
-            if container.as_ref()[j] > container.as_ref()[j + 1] {
+            if compare(&arr[j], &arr[j + 1]) == Ordering::Greater {
                 // Swap elements if they are in the wrong order
-                container.as_mut().swap(j, j + 1);
+                swap(arr, j, j + 1);
             }
         }
     }
 }
 
+pub fn bubblesort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    bubblesort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct BubbleSort;
+
+impl<T: PartialOrd> Sorter<T> for BubbleSort {
+    fn name(&self) -> &'static str {
+        "Bubble sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        bubblesort(arr);
+    }
+}
+
 
 
 
@@ -67,4 +95,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bubble_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        bubblesort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
 }
\ No newline at end of file
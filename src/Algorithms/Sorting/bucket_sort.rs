@@ -0,0 +1,146 @@
+/***
+ * Bucket Sort
+ * Sort for floats in a known range, O(n) expected on evenly spread input, O(n^2) worst case
+ *
+ * [min, max] is split into n equally wide buckets, every element goes to the bucket its
+ * key falls into and each bucket is finished with insertion sort. Evenly spread keys
+ * leave about one element per bucket.
+ * Keys outside the range are clamped into the first or last bucket, the result is still
+ * sorted but those buckets get slow. NaN keys compare as equal, like the other sorts.
+ *
+ * Upsides:
+ * - Linear time on uniformly distributed data
+ * - Stable
+ *
+ * Downsides:
+ * - Needs the key range up front
+ * - Degrades to insertion sort when keys clump together
+ */
+
+use super::apply_order;
+use super::insertion_sort::insertion_sort_range;
+use super::partial_order;
+use super::stats::{record_writes, Scratch};
+
+pub fn bucket_sort<T>(arr: &mut [T], min: f64, max: f64)
+where
+    T: Copy + Into<f64>,
+{
+    bucket_sort_by_key(arr, min, max, |&x| x.into());
+}
+
+pub fn bucket_sort_by_key<T, F>(arr: &mut [T], min: f64, max: f64, key: F)
+where
+    F: FnMut(&T) -> f64,
+{
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+
+    let keys: Vec<f64> = arr.iter().map(key).collect();
+    let buckets: Vec<usize> = keys.iter().map(|&k| bucket_of(k, min, max, len)).collect();
+
+    // counting pass, same as counting sort: bucket sizes -> first slot of every bucket
+    let mut starts = vec![0; len + 1];
+    for &b in buckets.iter() {
+        starts[b + 1] += 1;
+    }
+    for b in 0..len {
+        starts[b + 1] += starts[b];
+    }
+
+    let mut pairs = vec![(0.0, 0); len];
+    // keys, bucket of every key, bucket starts twice and the (key, index) pairs
+    let _scratch = Scratch::new::<f64>(len);
+    let _index_scratch = Scratch::new::<usize>(len + 2 * (len + 1));
+    let _pair_scratch = Scratch::new::<(f64, usize)>(len);
+    let mut next = starts.clone();
+    for (i, (&k, &b)) in keys.iter().zip(buckets.iter()).enumerate() {
+        pairs[next[b]] = (k, i);
+        next[b] += 1;
+    }
+    record_writes(pairs.as_ptr(), len);
+
+    for b in 0..len {
+        let bucket = &mut pairs[starts[b]..starts[b + 1]];
+        if bucket.len() > 1 {
+            // key order within a bucket, not counted as comparisons like the rest of this sort
+            insertion_sort_range(bucket, &mut |x: &(f64, usize), y: &(f64, usize)| partial_order(&x.0, &y.0));
+        }
+    }
+
+    apply_order(arr, pairs.into_iter().map(|p| p.1));
+}
+
+fn bucket_of(key: f64, min: f64, max: f64, buckets: usize) -> usize {
+    let scaled = (key - min) / (max - min) * buckets as f64;
+    // float to int casts saturate (NaN becomes 0), clamp the top end to the last bucket
+    (scaled as usize).min(buckets - 1)
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_bucket_sort() {
+        let mut arr = [0.5, 0.4, 0.3, 0.2, 0.1];
+        bucket_sort(&mut arr, 0.0, 1.0);
+        assert_eq!(arr, [0.1, 0.2, 0.3, 0.4, 0.5]);
+
+        let mut empty: [f32; 0] = [];
+        bucket_sort(&mut empty, 0.0, 1.0);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_bucket_sort_300() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<f32> = (0..300).map(|_| rng.gen_range(-10.0..10.0)).collect();
+        bucket_sort(&mut arr, -10.0, 10.0);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_bucket_sort_out_of_range() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<f64> = (0..500).map(|_| rng.gen_range(-5.0..15.0)).collect();
+        arr.extend([f64::INFINITY, f64::NEG_INFINITY, 10.0, 0.0]);
+        let mut expected = arr.clone();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        bucket_sort(&mut arr, 0.0, 10.0);
+        assert_eq!(arr, expected);
+    }
+
+    #[test]
+    fn test_bucket_sort_by_key_stable() {
+        let mut rng = thread_rng();
+        // few distinct prices, so buckets hold many equal keys
+        let arr: Vec<(f64, String)> = (0..1000).map(|i| (rng.gen_range(0..20) as f64 * 0.5, i.to_string())).collect();
+        let mut sorted = arr.clone();
+        bucket_sort_by_key(&mut sorted, 0.0, 10.0, |p| p.0);
+        let mut expected = arr;
+        expected.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        assert_eq!(sorted, expected);
+    }
+
+    // test 1000000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_bucket_sort_1000000() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<f64> = (0..1000000).map(|_| rng.gen()).collect();
+        bucket_sort(&mut arr, 0.0, 1.0);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+}
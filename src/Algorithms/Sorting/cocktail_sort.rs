@@ -0,0 +1,116 @@
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+pub fn cocktailsort<T: PartialOrd>(arr: &mut [T]) {
+    cocktailsort_by(arr, partial_order);
+}
+
+// bubble sort in both directions, the sorted region grows from both ends
+pub fn cocktailsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    if arr.len() < 2 {
+        return;
+    }
+    // arr[..start] and arr[end + 1..] are already in their final place
+    let mut start = 0;
+    let mut end = arr.len() - 1;
+
+    while start < end {
+        // forward pass carries the largest element up to end
+        let mut last_swap = start;
+        for i in start..end {
+            if compare(&arr[i], &arr[i + 1]) == Ordering::Greater {
+                swap(arr, i, i + 1);
+                last_swap = i;
+            }
+        }
+        end = last_swap;
+
+        // backward pass carries the smallest element down to start
+        let mut first_swap = end;
+        for i in (start..end).rev() {
+            if compare(&arr[i], &arr[i + 1]) == Ordering::Greater {
+                swap(arr, i, i + 1);
+                first_swap = i + 1;
+            }
+        }
+        start = first_swap;
+    }
+}
+
+pub fn cocktailsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    cocktailsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct CocktailSort;
+
+impl<T: PartialOrd> Sorter<T> for CocktailSort {
+    fn name(&self) -> &'static str {
+        "Cocktail shaker sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        cocktailsort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_cocktail_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        cocktailsort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        cocktailsort(&mut empty);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_cocktail_sort_300() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        cocktailsort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    // test 10000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_cocktail_sort_10000() {
+        let mut arr: Vec<_> = (0..10000).collect();
+        arr.shuffle(&mut thread_rng());
+        cocktailsort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_cocktail_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        cocktailsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,107 @@
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+// the gap shrinks by this factor every pass, 1.3 is the usual empirically best value
+const SHRINK_FACTOR: f64 = 1.3;
+
+pub fn combsort<T: PartialOrd>(arr: &mut [T]) {
+    combsort_by(arr, partial_order);
+}
+
+// bubble sort over elements gap apart, so small values at the end (turtles) move fast
+pub fn combsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let len = arr.len();
+    let mut gap = len;
+    let mut sorted = false;
+
+    while !sorted {
+        gap = ((gap as f64 / SHRINK_FACTOR) as usize).max(1);
+        // done once a plain bubble pass (gap 1) makes no swaps
+        sorted = gap == 1;
+
+        for i in 0..len.saturating_sub(gap) {
+            if compare(&arr[i], &arr[i + gap]) == Ordering::Greater {
+                swap(arr, i, i + gap);
+                sorted = false;
+            }
+        }
+    }
+}
+
+pub fn combsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    combsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct CombSort;
+
+impl<T: PartialOrd> Sorter<T> for CombSort {
+    fn name(&self) -> &'static str {
+        "Comb sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        combsort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_comb_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        combsort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        combsort(&mut empty);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_comb_sort_300() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        combsort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    // test 10000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_comb_sort_10000() {
+        let mut arr: Vec<_> = (0..10000).collect();
+        arr.shuffle(&mut thread_rng());
+        combsort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_comb_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        combsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+}
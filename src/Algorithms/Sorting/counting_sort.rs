@@ -0,0 +1,192 @@
+/***
+ * Counting Sort
+ * Non-comparison integer sort, O(n + k) time and O(n + k) memory where k = max - min + 1
+ *
+ * Counts how often every key between min and max occurs, prefix sums turn the counts
+ * into the first output slot of each key and every element is moved straight there.
+ * Stable, equal keys keep their relative order.
+ *
+ * A key range much wider than the input would make the count table dominate,
+ * in that case the sort hands over to LSD radix sort instead.
+ *
+ * Upsides:
+ * - Linear time, the fastest option for small key ranges (ages, bytes, enum tags ...)
+ *
+ * Downsides:
+ * - Memory grows with the key range, not just the input
+ */
+
+use super::apply_order;
+use super::radix_sort::{radix_sort, sort_by_radix_keys, RadixKey};
+use super::sorter::Sorter;
+use super::stats::{record_writes, Scratch};
+
+// the count table may be this many times larger than the input before radix sort takes over
+const MAX_RANGE_FACTOR: u64 = 4;
+// small inputs always get a table of at least this many slots
+const MIN_RANGE: u64 = 1 << 16;
+
+pub fn counting_sort<T: RadixKey>(arr: &mut [T]) {
+    let Some((min, range)) = key_range(arr.iter().map(|x| x.to_radix()), arr.len()) else {
+        radix_sort(arr);
+        return;
+    };
+
+    let counts = count_keys(arr.iter().map(|x| x.to_radix() - min), range);
+    let mut offsets = prefix_sums(counts);
+    let mut buf = arr.to_vec();
+    let _scratch = Scratch::new::<usize>(range);
+    let _buf_scratch = Scratch::new::<T>(buf.len());
+    for &x in arr.iter() {
+        let slot = &mut offsets[(x.to_radix() - min) as usize];
+        buf[*slot] = x;
+        *slot += 1;
+    }
+    record_writes(buf.as_ptr(), buf.len());
+    arr.copy_from_slice(&buf);
+    record_writes(arr.as_ptr(), arr.len());
+}
+
+pub fn counting_sort_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    F: FnMut(&T) -> K,
+    K: RadixKey,
+{
+    let keys: Vec<u64> = arr.iter().map(key).map(K::to_radix).collect();
+    let _keys_scratch = Scratch::new::<u64>(keys.len());
+    let Some((min, range)) = key_range(keys.iter().copied(), keys.len()) else {
+        sort_by_radix_keys(arr, keys, K::BYTES, false);
+        return;
+    };
+
+    let counts = count_keys(keys.iter().map(|&k| k - min), range);
+    let mut offsets = prefix_sums(counts);
+    // order[slot] = index of the element that belongs in slot
+    let mut order = vec![0; keys.len()];
+    let _scratch = Scratch::new::<usize>(range + order.len());
+    for (i, &k) in keys.iter().enumerate() {
+        let slot = &mut offsets[(k - min) as usize];
+        order[*slot] = i;
+        *slot += 1;
+    }
+    apply_order(arr, order);
+}
+
+// (min, max - min + 1) of the keys, None if the range is too wide for a count table
+fn key_range<I: Iterator<Item = u64>>(keys: I, len: usize) -> Option<(u64, usize)> {
+    let (min, max) = keys.fold((u64::MAX, 0), |(min, max), k| (min.min(k), max.max(k)));
+    if min > max {
+        // no keys at all
+        return Some((0, 0));
+    }
+
+    let span = max - min;
+    if span >= MIN_RANGE.max(len as u64 * MAX_RANGE_FACTOR) {
+        return None;
+    }
+    Some((min, span as usize + 1))
+}
+
+fn count_keys<I: Iterator<Item = u64>>(keys: I, range: usize) -> Vec<usize> {
+    let mut counts = vec![0; range];
+    for k in keys {
+        counts[k as usize] += 1;
+    }
+    counts
+}
+
+// turns counts into the first output slot of every key, in place
+fn prefix_sums(mut counts: Vec<usize>) -> Vec<usize> {
+    let mut sum = 0;
+    for c in counts.iter_mut() {
+        let count = *c;
+        *c = sum;
+        sum += count;
+    }
+    counts
+}
+
+pub struct CountingSort;
+
+impl<T: RadixKey> Sorter<T> for CountingSort {
+    fn name(&self) -> &'static str {
+        "Counting sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        counting_sort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_counting_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        counting_sort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [u8; 0] = [];
+        counting_sort(&mut empty);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_counting_sort_300() {
+        let mut arr: Vec<i32> = (-150..150).collect();
+        arr.shuffle(&mut thread_rng());
+        counting_sort(&mut arr);
+        assert_eq!(arr, (-150..150).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_counting_sort_wide_range_falls_back() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<u64> = (0..500).map(|_| rng.gen()).collect();
+        arr.push(u64::MAX);
+        arr.push(0);
+        let mut expected = arr.clone();
+        expected.sort();
+        counting_sort(&mut arr);
+        assert_eq!(arr, expected);
+    }
+
+    #[test]
+    fn test_counting_sort_by_key_stable() {
+        let mut rng = thread_rng();
+        let arr: Vec<(u8, String)> = (0..1000).map(|i| (rng.gen_range(0..10), i.to_string())).collect();
+        let mut sorted = arr.clone();
+        counting_sort_by_key(&mut sorted, |p| p.0);
+        let mut expected = arr;
+        expected.sort_by_key(|p| p.0);
+        assert_eq!(sorted, expected);
+
+        // wide keys go through radix sort, still stable
+        let arr: Vec<(i64, usize)> = (0..1000).map(|i| (rng.gen_range(-3..3) * (i64::MAX / 4), i)).collect();
+        let mut sorted = arr.clone();
+        counting_sort_by_key(&mut sorted, |p| p.0);
+        let mut expected = arr;
+        expected.sort_by_key(|p| p.0);
+        assert_eq!(sorted, expected);
+    }
+
+    // test 1000000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_counting_sort_1000000() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<u32> = (0..1000000).map(|_| rng.gen_range(0..100000)).collect();
+        let mut expected = arr.clone();
+        expected.sort();
+        counting_sort(&mut arr);
+        assert_eq!(arr, expected);
+    }
+}
@@ -0,0 +1,400 @@
+/***
+ * External Sort
+ * Merge sort for fixed width records that don't fit in memory, O(n log n) time, O(chunk) memory
+ *
+ * Two phases:
+ * - runs: the input is read one chunk of records at a time, every chunk is sorted in memory
+ *   with timsort and spilled to a temporary run file. Input that fits in one chunk is written
+ *   straight to the output, no files involved.
+ * - merge: the runs are k-way merged through a min BinaryHeap holding the next record of every
+ *   run. With more runs than fan_in they are first merged in groups of fan_in into longer runs,
+ *   so the number of open files stays bounded.
+ *
+ * Stable: equal records leave the heap in run order and runs are cut from the input in order.
+ * Run files are created in temp_dir and removed again once merged, also when the sort fails.
+ * An input that ends in the middle of a record is rejected with ErrorKind::InvalidData.
+ *
+ * Upsides:
+ * - Memory use is fixed by the config, not by the input
+ * - Input and output are any Read / Write, files, sockets, in memory buffers
+ *
+ * Downsides:
+ * - Every record is read and written to disk at least twice once it spills
+ * - Records have a fixed width, variable length data needs to be padded or indexed
+ */
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use crate::containers::trees::binary_heap::{BinaryHeap, HeapType};
+use super::partial_order;
+use super::tim_sort::timsort_by;
+
+// default memory for one chunk of records
+const CHUNK_BYTES: usize = 64 << 20;
+// default number of runs merged at once
+const FAN_IN: usize = 64;
+
+// makes run file names unique within the process, the pid covers other processes
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSortConfig {
+    pub record_size: usize,   // bytes per record
+    pub chunk_records: usize, // records sorted in memory at once, the memory budget
+    pub fan_in: usize,        // runs merged at once, at least 2
+    pub temp_dir: PathBuf,    // where run files go
+}
+
+impl ExternalSortConfig {
+    // about 64 MiB chunks, 64 way merges, runs in the system temp dir
+    pub fn new(record_size: usize) -> Self {
+        ExternalSortConfig {
+            record_size,
+            chunk_records: (CHUNK_BYTES / record_size.max(1)).max(1),
+            fan_in: FAN_IN,
+            temp_dir: env::temp_dir(),
+        }
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        if self.record_size == 0 || self.chunk_records == 0 || self.fan_in < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record_size and chunk_records must be positive and fan_in at least 2",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// records in byte order, returns the number of records written
+pub fn external_sort<R, W>(input: R, output: W, config: &ExternalSortConfig) -> io::Result<u64>
+where
+    R: Read,
+    W: Write,
+{
+    external_sort_by(input, output, config, |a, b| a.cmp(b))
+}
+
+pub fn external_sort_by<R, W, F>(mut input: R, mut output: W, config: &ExternalSortConfig, compare: F) -> io::Result<u64>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&[u8], &[u8]) -> Ordering,
+{
+    config.validate()?;
+    let record_size = config.record_size;
+    // shared by the chunk sorts and the heap entries
+    let compare = RefCell::new(compare);
+
+    let mut chunk = vec![0u8; record_size * config.chunk_records];
+    let mut runs = Vec::new();
+    let mut total = 0;
+    loop {
+        let filled = fill(&mut input, &mut chunk)?;
+        if filled % record_size != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "input ends in the middle of a record"));
+        }
+        if filled == 0 {
+            break;
+        }
+        total += (filled / record_size) as u64;
+
+        let mut records: Vec<&[u8]> = chunk[..filled].chunks_exact(record_size).collect();
+        timsort_by(&mut records, |a, b| (compare.borrow_mut())(a, b));
+
+        if runs.is_empty() && filled < chunk.len() {
+            // the whole input fit in one chunk
+            for record in records {
+                output.write_all(record)?;
+            }
+            output.flush()?;
+            return Ok(total);
+        }
+
+        let (run, mut writer) = RunFile::create(&config.temp_dir)?;
+        for record in records {
+            writer.write_all(record)?;
+        }
+        writer.flush()?;
+        runs.push(run);
+
+        if filled < chunk.len() {
+            break;
+        }
+    }
+    // the merge only needs one record per run
+    drop(chunk);
+
+    while runs.len() > config.fan_in {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(config.fan_in));
+        for group in runs.chunks(config.fan_in) {
+            let (run, mut writer) = RunFile::create(&config.temp_dir)?;
+            merge_runs(group, &mut writer, record_size, &compare)?;
+            writer.flush()?;
+            merged.push(run);
+        }
+        // dropping the merged runs removes their files
+        runs = merged;
+    }
+
+    merge_runs(&runs, &mut output, record_size, &compare)?;
+    output.flush()?;
+    Ok(total)
+}
+
+pub fn external_sort_by_key<R, W, K, F>(input: R, output: W, config: &ExternalSortConfig, mut key: F) -> io::Result<u64>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&[u8]) -> K,
+    K: PartialOrd,
+{
+    external_sort_by(input, output, config, |a, b| partial_order(&key(a), &key(b)))
+}
+
+// reads until buf is full or the input ends, returns the number of bytes read
+fn fill<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+// next record of a run into record, false once the run is exhausted
+fn read_record<R: Read>(input: &mut R, record: &mut [u8]) -> io::Result<bool> {
+    match fill(input, record)? {
+        0 => Ok(false),
+        n if n == record.len() => Ok(true),
+        _ => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "run file ends in the middle of a record")),
+    }
+}
+
+// k-way merge, the heap holds the next record of every run that isn't exhausted yet
+fn merge_runs<W, F>(runs: &[RunFile], output: &mut W, record_size: usize, compare: &RefCell<F>) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(&[u8], &[u8]) -> Ordering,
+{
+    let mut readers = runs.iter().map(RunFile::open).collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::with_capacity(readers.len(), HeapType::Min);
+    for (run, reader) in readers.iter_mut().enumerate() {
+        let mut record = vec![0; record_size];
+        if read_record(reader, &mut record)? {
+            heap.push(Head { record, run, compare });
+        }
+    }
+
+    let Some(mut head) = heap.pop() else {
+        return Ok(());
+    };
+    loop {
+        output.write_all(&head.record)?;
+        // refill from the same run, the next smallest is either that record or the heap top
+        head = if read_record(&mut readers[head.run], &mut head.record)? {
+            heap.push_pop(head)
+        } else {
+            match heap.pop() {
+                Some(next) => next,
+                None => return Ok(()),
+            }
+        };
+    }
+}
+
+// a record waiting in the merge heap and the run it came from
+struct Head<'a, F> {
+    record: Vec<u8>,
+    run: usize,
+    compare: &'a RefCell<F>,
+}
+
+impl<F: FnMut(&[u8], &[u8]) -> Ordering> PartialEq for Head<'_, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<F: FnMut(&[u8], &[u8]) -> Ordering> PartialOrd for Head<'_, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let order = (self.compare.borrow_mut())(&self.record, &other.record);
+        // equal records leave in run order, that keeps the sort stable
+        Some(order.then(self.run.cmp(&other.run)))
+    }
+}
+
+// a temporary file holding one sorted run, removed on drop
+struct RunFile {
+    path: PathBuf,
+}
+
+impl RunFile {
+    fn create(dir: &Path) -> io::Result<(RunFile, BufWriter<File>)> {
+        let id = RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("omni_arsenal_run_{}_{}.tmp", process::id(), id));
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        Ok((RunFile { path }, BufWriter::new(file)))
+    }
+
+    fn open(&self) -> io::Result<BufReader<File>> {
+        Ok(BufReader::new(File::open(&self.path)?))
+    }
+}
+
+impl Drop for RunFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+    use std::io::Cursor;
+
+    // a fresh directory per test, so leftover run files can be spotted
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("omni_arsenal_test_{}_{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn small_config(record_size: usize, dir: &Path) -> ExternalSortConfig {
+        ExternalSortConfig {
+            record_size,
+            chunk_records: 10,
+            fan_in: 3,
+            temp_dir: dir.to_path_buf(),
+        }
+    }
+
+    fn records(input: &[u8], record_size: usize) -> Vec<Vec<u8>> {
+        input.chunks(record_size).map(|r| r.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_external_sort() {
+        let input: Vec<u8> = [5u32, 4, 3, 2, 1].iter().flat_map(|x| x.to_be_bytes()).collect();
+        let mut output = Vec::new();
+        let count = external_sort(&input[..], &mut output, &ExternalSortConfig::new(4)).unwrap();
+        assert_eq!(count, 5);
+        let expected: Vec<u8> = [1u32, 2, 3, 4, 5].iter().flat_map(|x| x.to_be_bytes()).collect();
+        assert_eq!(output, expected);
+
+        let mut output = Vec::new();
+        assert_eq!(external_sort(&[][..], &mut output, &ExternalSortConfig::new(4)).unwrap(), 0);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_external_sort_spills_and_merges() {
+        let dir = temp_dir("spills");
+        let mut rng = thread_rng();
+        // exactly on and off chunk boundaries, 1 run up to multi pass merges
+        for len in [10, 25, 30, 300, 1001] {
+            let input: Vec<u8> = (0..len * 8).map(|_| rng.gen()).collect();
+            let mut output = Vec::new();
+            let count = external_sort(Cursor::new(&input), &mut output, &small_config(8, &dir)).unwrap();
+            assert_eq!(count, len as u64);
+
+            let mut expected = records(&input, 8);
+            expected.sort();
+            assert_eq!(records(&output, 8), expected);
+        }
+        // every run file is gone again
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_external_sort_by_key_stable() {
+        let dir = temp_dir("stable");
+        let mut rng = thread_rng();
+        // 1 byte key, then the position in the input
+        let input: Vec<u8> = (0..500u32)
+            .flat_map(|i| {
+                let mut record = vec![rng.gen_range(0..5)];
+                record.extend(i.to_be_bytes());
+                record
+            })
+            .collect();
+
+        let mut output = Vec::new();
+        external_sort_by_key(&input[..], &mut output, &small_config(5, &dir), |r| r[0]).unwrap();
+        let mut expected = records(&input, 5);
+        expected.sort_by_key(|r| r[0]);
+        assert_eq!(records(&output, 5), expected);
+
+        // descending by the position field
+        let mut output = Vec::new();
+        external_sort_by(&input[..], &mut output, &small_config(5, &dir), |a, b| b[1..].cmp(&a[1..])).unwrap();
+        let positions: Vec<u32> = records(&output, 5).iter().map(|r| u32::from_be_bytes(r[1..].try_into().unwrap())).collect();
+        assert_eq!(positions, (0..500).rev().collect::<Vec<_>>());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_external_sort_errors() {
+        let dir = temp_dir("errors");
+        let mut output = Vec::new();
+
+        // 25 whole records and a stray byte, fails after some runs were spilled
+        let input = [1u8; 4 * 25 + 1];
+        let err = external_sort(&input[..], &mut output, &small_config(4, &dir)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let mut config = small_config(4, &dir);
+        config.fan_in = 1;
+        assert_eq!(external_sort(&[][..], &mut output, &config).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            external_sort(&[][..], &mut output, &ExternalSortConfig::new(0)).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        // a temp dir that doesn't exist
+        config.fan_in = 2;
+        config.temp_dir = dir.join("missing");
+        assert!(external_sort(&[0u8; 400][..], &mut output, &config).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // test 1000000 records
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_external_sort_1000000() {
+        let dir = temp_dir("heavy");
+        let mut rng = thread_rng();
+        let input: Vec<u8> = (0..1000000 * 16).map(|_| rng.gen()).collect();
+        let config = ExternalSortConfig {
+            chunk_records: 50000,
+            fan_in: 8,
+            temp_dir: dir.clone(),
+            ..ExternalSortConfig::new(16)
+        };
+        let mut output = Vec::new();
+        assert_eq!(external_sort(&input[..], &mut output, &config).unwrap(), 1000000);
+        for w in output.chunks(16).collect::<Vec<_>>().windows(2) {
+            assert!(w[0] <= w[1]);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
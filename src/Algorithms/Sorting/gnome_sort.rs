@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+pub fn gnomesort<T: PartialOrd>(arr: &mut [T]) {
+    gnomesort_by(arr, partial_order);
+}
+
+// steps forward while the pair under the gnome is in order, otherwise swaps and steps back
+pub fn gnomesort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let len = arr.len();
+    let mut pos = 1;
+
+    while pos < len {
+        if pos == 0 || compare(&arr[pos - 1], &arr[pos]) != Ordering::Greater {
+            pos += 1;
+        } else {
+            swap(arr, pos - 1, pos);
+            pos -= 1;
+        }
+    }
+}
+
+pub fn gnomesort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    gnomesort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct GnomeSort;
+
+impl<T: PartialOrd> Sorter<T> for GnomeSort {
+    fn name(&self) -> &'static str {
+        "Gnome sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        gnomesort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_gnome_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        gnomesort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        gnomesort(&mut empty);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_gnome_sort_300() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        gnomesort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    // test 10000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_gnome_sort_10000() {
+        let mut arr: Vec<_> = (0..10000).collect();
+        arr.shuffle(&mut thread_rng());
+        gnomesort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_gnome_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        gnomesort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+}
@@ -1,42 +1,85 @@
 
-use std::ops::Index;
-pub fn heapsort<T, O>(container: &mut T)
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+pub fn heapsort<T: PartialOrd>(arr: &mut [T]) {
+    heapsort_by(arr, partial_order);
+}
+
+pub fn heapsort_by<T, F>(arr: &mut [T], compare: F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized,
-    O: PartialOrd,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    let len = container.as_ref().len();
+    heap_sort_range(arr, &mut counted(compare));
+}
+
+pub fn heapsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    heapsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+// heapsort_by without counting, for the sorts that fall back to it with an already counted comparator
+pub(super) fn heap_sort_range<T, F>(arr: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
 
     for i in (0..len / 2).rev() {
-        heapify(container, len, i);
+        heapify(arr, len, i, compare);
     }
 
-    for i in (0..len).rev() {
-        container.as_mut().swap(0, i);
-        heapify(container, i, 0);
+    sort_heap(arr, compare);
+}
+
+// arr is a max heap, repeatedly moves the root behind the shrinking heap, leaving arr sorted
+pub(super) fn sort_heap<T, F>(arr: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in (0..arr.len()).rev() {
+        swap(arr, 0, i);
+        heapify(arr, i, 0, compare);
     }
 }
 
-fn heapify<T, O>(container: &mut T, n: usize, i: usize)
+// sifts arr[i] down into the max heap arr[..n]
+pub(super) fn heapify<T, F>(arr: &mut [T], n: usize, i: usize, compare: &mut F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized,
-    O: PartialOrd,
+    F: FnMut(&T, &T) -> Ordering,
 {
     let mut largest = i;
     let left = 2 * i + 1;
     let right = 2 * i + 2;
 
-    if left < n && container.as_ref()[left] > container.as_ref()[largest] {
+    if left < n && compare(&arr[left], &arr[largest]) == Ordering::Greater {
         largest = left;
     }
 
-    if right < n && container.as_ref()[right] > container.as_ref()[largest] {
+    if right < n && compare(&arr[right], &arr[largest]) == Ordering::Greater {
         largest = right;
     }
 
     if largest != i {
-        container.as_mut().swap(i, largest);
-        heapify(container, n, largest);
+        swap(arr, i, largest);
+        heapify(arr, n, largest, compare);
+    }
+}
+
+pub struct HeapSort;
+
+impl<T: PartialOrd> Sorter<T> for HeapSort {
+    fn name(&self) -> &'static str {
+        "Heap sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        heapsort(arr);
     }
 }
 
@@ -90,4 +133,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_heap_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        heapsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
 }
\ No newline at end of file
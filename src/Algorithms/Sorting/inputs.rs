@@ -0,0 +1,315 @@
+/***
+ * Inputs
+ * Input generators for the benches and tests, the shapes that make or break a sort
+ *
+ * - random: uniformly shuffled 0..len
+ * - sorted / reversed: the classic worst cases for naive pivots and adaptive sorts
+ * - sawtooth: ascending runs that restart every len / teeth elements
+ * - organ_pipe: ascending to the middle then descending, 0 1 2 .. 2 1 0
+ * - few_unique: only a handful of distinct keys, stresses equal key handling
+ * - many_duplicates: about sqrt(len) distinct keys, each repeated about sqrt(len) times
+ * - almost_sorted: sorted with k random pairs swapped
+ * - mcilroy_killer: McIlroy's adversary ("A Killer Adversary for Quicksort", 1999), builds the
+ *   input that drives a given deterministic quicksort to its quadratic worst case
+ * - random_strings: short random lowercase strings, comparisons cost more than a word compare
+ * - large_structs: 128 byte records sorted by key, moves cost more than comparisons
+ *
+ * Every generator is seeded from its arguments, the same call always returns the same
+ * input so bench runs stay comparable.
+ */
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use super::quick_sort::quicksort_by;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    Random,
+    Sorted,
+    Reversed,
+    Sawtooth,
+    OrganPipe,
+    FewUnique,
+    ManyDuplicates,
+    AlmostSorted,
+    QuicksortKiller,
+}
+
+impl Pattern {
+    // every pattern, in the order the benches report them
+    pub const ALL: [Pattern; 9] = [
+        Pattern::Random,
+        Pattern::Sorted,
+        Pattern::Reversed,
+        Pattern::Sawtooth,
+        Pattern::OrganPipe,
+        Pattern::FewUnique,
+        Pattern::ManyDuplicates,
+        Pattern::AlmostSorted,
+        Pattern::QuicksortKiller,
+    ];
+
+    // human readable name, also used as part of the bench id
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::Random => "random",
+            Pattern::Sorted => "sorted",
+            Pattern::Reversed => "reversed",
+            Pattern::Sawtooth => "sawtooth",
+            Pattern::OrganPipe => "organ pipe",
+            Pattern::FewUnique => "few unique",
+            Pattern::ManyDuplicates => "many duplicates",
+            Pattern::AlmostSorted => "almost sorted",
+            Pattern::QuicksortKiller => "quicksort killer",
+        }
+    }
+
+    // the pattern with its default parameters, the killer targets quicksort
+    pub fn generate(&self, len: usize) -> Vec<i32> {
+        match self {
+            Pattern::Random => random(len),
+            Pattern::Sorted => sorted(len),
+            Pattern::Reversed => reversed(len),
+            Pattern::Sawtooth => sawtooth(len, 8),
+            Pattern::OrganPipe => organ_pipe(len),
+            Pattern::FewUnique => few_unique(len, 4),
+            Pattern::ManyDuplicates => many_duplicates(len),
+            Pattern::AlmostSorted => almost_sorted(len, len / 100 + 1),
+            Pattern::QuicksortKiller => mcilroy_killer(len, |arr, compare| quicksort_by(arr, compare)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LargeStruct {
+    pub key: u64,
+    pub payload: [u64; 15], // dead weight, derived from key so equal keys stay equal structs
+}
+
+pub fn random(len: usize) -> Vec<i32> {
+    let mut arr = sorted(len);
+    arr.shuffle(&mut rng(len, 0));
+    arr
+}
+
+pub fn sorted(len: usize) -> Vec<i32> {
+    (0..len as i32).collect()
+}
+
+pub fn reversed(len: usize) -> Vec<i32> {
+    (0..len as i32).rev().collect()
+}
+
+// teeth is clamped to 1..=len
+pub fn sawtooth(len: usize, teeth: usize) -> Vec<i32> {
+    let tooth = len.div_ceil(teeth.clamp(1, len.max(1))).max(1);
+    (0..len).map(|i| (i % tooth) as i32).collect()
+}
+
+pub fn organ_pipe(len: usize) -> Vec<i32> {
+    (0..len).map(|i| i.min(len - 1 - i) as i32).collect()
+}
+
+// keys drawn from 0..distinct, distinct is at least 1
+pub fn few_unique(len: usize, distinct: usize) -> Vec<i32> {
+    let distinct = distinct.max(1) as i32;
+    let mut rng = rng(len, distinct as u64);
+    (0..len).map(|_| rng.gen_range(0..distinct)).collect()
+}
+
+pub fn many_duplicates(len: usize) -> Vec<i32> {
+    let distinct = (len as f64).sqrt() as usize;
+    few_unique(len, distinct)
+}
+
+// sorted 0..len with swaps random pairs exchanged, so at most 2 * swaps elements are out of place
+pub fn almost_sorted(len: usize, swaps: usize) -> Vec<i32> {
+    let mut arr = sorted(len);
+    if len < 2 {
+        return arr;
+    }
+    let mut rng = rng(len, swaps as u64);
+    for _ in 0..swaps {
+        let a = rng.gen_range(0..len);
+        let b = rng.gen_range(0..len);
+        arr.swap(a, b);
+    }
+    arr
+}
+
+/*
+    McIlroy's adversary. sort is run once on the indices 0..len with a comparator that decides
+    the values lazily: every element starts out as "gas" (bigger than anything solid), and when
+    two gas elements meet the one that isn't the current pivot candidate gets frozen to the
+    next smallest solid value. The pivot stays gas as long as possible, so every partition
+    splits off almost nothing. The values fixed along the way, in index order, are an input
+    that makes sort take the same quadratic path again.
+
+    Only meaningful for sorts that are deterministic and see nothing but the comparator,
+    a randomized pivot gets a different input every time.
+*/
+pub fn mcilroy_killer<F>(len: usize, sort: F) -> Vec<i32>
+where
+    F: FnOnce(&mut [usize], &mut dyn FnMut(&usize, &usize) -> Ordering),
+{
+    let gas = len as i32;
+    let state = RefCell::new(Adversary { values: vec![gas; len], solid: 0, candidate: 0 });
+    let mut indices: Vec<usize> = (0..len).collect();
+
+    let mut compare = |&a: &usize, &b: &usize| {
+        let mut state = state.borrow_mut();
+        if state.values[a] == gas && state.values[b] == gas {
+            let freeze = if a == state.candidate { a } else { b };
+            state.freeze(freeze);
+        }
+        if state.values[a] == gas {
+            state.candidate = a;
+        } else if state.values[b] == gas {
+            state.candidate = b;
+        }
+        state.values[a].cmp(&state.values[b])
+    };
+    sort(&mut indices, &mut compare);
+
+    // whatever the sort never had to look at closely gets the remaining values
+    let mut state = state.into_inner();
+    for i in 0..len {
+        if state.values[i] == gas {
+            state.freeze(i);
+        }
+    }
+    state.values
+}
+
+// lowercase ascii strings of 1..=max_len characters
+pub fn random_strings(len: usize, max_len: usize) -> Vec<String> {
+    let max_len = max_len.max(1);
+    let mut rng = rng(len, max_len as u64);
+    (0..len)
+        .map(|_| {
+            let n = rng.gen_range(1..=max_len);
+            (0..n).map(|_| rng.gen_range(b'a'..=b'z') as char).collect()
+        })
+        .collect()
+}
+
+// shuffled keys 0..len
+pub fn large_structs(len: usize) -> Vec<LargeStruct> {
+    random(len)
+        .into_iter()
+        .map(|key| {
+            let key = key as u64;
+            LargeStruct { key, payload: [key; 15] }
+        })
+        .collect()
+}
+
+struct Adversary {
+    values: Vec<i32>,
+    solid: i32,       // next value handed out by freeze
+    candidate: usize, // gas element the sort most likely uses as its pivot
+}
+
+impl Adversary {
+    fn freeze(&mut self, i: usize) {
+        self.values[i] = self.solid;
+        self.solid += 1;
+    }
+}
+
+fn rng(len: usize, salt: u64) -> StdRng {
+    StdRng::seed_from_u64((len as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ salt)
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::sorting::quick_sort::{introsort, introsort_by, quicksort, quicksort_with, quicksort_with_by, PivotStrategy};
+    use crate::algorithms::sorting::stats::measure;
+
+    fn is_permutation_of_range(arr: &[i32]) -> bool {
+        let mut arr = arr.to_vec();
+        arr.sort();
+        arr == sorted(arr.len())
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(sorted(5), vec![0, 1, 2, 3, 4]);
+        assert_eq!(reversed(5), vec![4, 3, 2, 1, 0]);
+        assert_eq!(sawtooth(9, 3), vec![0, 1, 2, 0, 1, 2, 0, 1, 2]);
+        assert_eq!(organ_pipe(6), vec![0, 1, 2, 2, 1, 0]);
+        assert!(is_permutation_of_range(&random(500)));
+        assert!(is_permutation_of_range(&almost_sorted(500, 5)));
+        assert!(few_unique(500, 4).iter().all(|&x| (0..4).contains(&x)));
+        assert!(many_duplicates(400).iter().all(|&x| (0..20).contains(&x)));
+        assert!(random_strings(100, 8).iter().all(|s| (1..=8).contains(&s.len())));
+        assert_eq!(large_structs(10).iter().map(|s| s.key).max(), Some(9));
+    }
+
+    #[test]
+    fn generators_are_deterministic() {
+        for pattern in Pattern::ALL {
+            assert_eq!(pattern.generate(200), pattern.generate(200), "{}", pattern.name());
+        }
+        assert_eq!(random_strings(50, 10), random_strings(50, 10));
+    }
+
+    #[test]
+    fn empty_and_single() {
+        for pattern in Pattern::ALL {
+            assert!(pattern.generate(0).is_empty(), "{}", pattern.name());
+            assert_eq!(pattern.generate(1), vec![0], "{}", pattern.name());
+        }
+        assert_eq!(sawtooth(3, 0), vec![0, 1, 2]);
+        assert_eq!(almost_sorted(1, 10), vec![0]);
+    }
+
+    #[test]
+    fn almost_sorted_is_almost_sorted() {
+        let arr = almost_sorted(1000, 3);
+        let misplaced = arr.iter().enumerate().filter(|&(i, &x)| i as i32 != x).count();
+        assert!(misplaced <= 6);
+    }
+
+    #[test]
+    fn killer_is_a_permutation() {
+        let arr = mcilroy_killer(300, |arr, compare| quicksort_by(arr, compare));
+        assert!(is_permutation_of_range(&arr));
+    }
+
+    #[test]
+    fn killer_makes_quicksort_quadratic() {
+        let len = 2000;
+        let shuffled = measure(&mut random(len), quicksort).comparisons;
+
+        let mut arr = Pattern::QuicksortKiller.generate(len);
+        let killed = measure(&mut arr, quicksort).comparisons;
+        assert!(killed > 10 * shuffled, "killer {} vs random {}", killed, shuffled);
+        assert_eq!(arr, sorted(len));
+
+        // textbook last element pivots go down the same way
+        let mut arr = mcilroy_killer(len, |arr, compare| quicksort_with_by(arr, PivotStrategy::Last, compare));
+        let killed = measure(&mut arr, |arr| quicksort_with(arr, PivotStrategy::Last)).comparisons;
+        assert!(killed > 10 * shuffled, "killer {} vs random {}", killed, shuffled);
+    }
+
+    #[test]
+    fn introsort_survives_its_killer() {
+        let len = 2000;
+        let shuffled = measure(&mut random(len), introsort).comparisons;
+
+        let mut arr = mcilroy_killer(len, |arr, compare| introsort_by(arr, compare));
+        let killed = measure(&mut arr, introsort).comparisons;
+        // falls back to heapsort before it gets quadratic
+        assert!(killed < 5 * shuffled, "killer {} vs random {}", killed, shuffled);
+        assert_eq!(arr, sorted(len));
+    }
+}
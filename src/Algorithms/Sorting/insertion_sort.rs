@@ -1,20 +1,55 @@
-use std::ops::Index;
-pub fn insertionsort<T, O>(container: &mut T)    
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+pub fn insertionsort<T: PartialOrd>(arr: &mut [T]) {
+    insertionsort_by(arr, partial_order);
+}
+
+pub fn insertionsort_by<T, F>(arr: &mut [T], compare: F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized,
-    O: PartialOrd,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    let len = container.as_ref().len();
+    insertion_sort_range(arr, &mut counted(compare));
+}
+
+// insertionsort_by without counting, for the sorts that finish small ranges with it
+pub(super) fn insertion_sort_range<T, F>(arr: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
 
     for i in 1..len {
         let mut j = i;
-        while j > 0 && container.as_ref()[j - 1] > container.as_ref()[j] {
-            container.as_mut().swap(j - 1, j);
+        while j > 0 && compare(&arr[j - 1], &arr[j]) == Ordering::Greater {
+            swap(arr, j - 1, j);
             j -= 1;
         }
     }
 }
 
+pub fn insertionsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    insertionsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct InsertionSort;
+
+impl<T: PartialOrd> Sorter<T> for InsertionSort {
+    fn name(&self) -> &'static str {
+        "Insertion sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        insertionsort(arr);
+    }
+}
+
 
 
 // **********  TESTS ********** //
@@ -65,4 +100,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_insertion_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        insertionsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
 }
\ No newline at end of file
@@ -1,45 +1,297 @@
-use std::ops::Index;
-pub fn mergesort<T, O>(container: &mut T)
+/***
+ * Merge Sort
+ * Stable, O(n log n) in all cases, O(n / 2) extra memory
+ *
+ * One scratch buffer of n / 2 slots is allocated per sort and reused by every merge.
+ * Elements are moved (bitwise) between the slice and the buffer instead of cloned,
+ * so T doesn't need Clone.
+ *
+ * Variants:
+ * - mergesort: top-down recursive, a stable sorting network for short ranges
+ * - mergesort_bottom_up: iterative, merges blocks of doubling width, no recursion
+ * - mergesort_natural: detects existing ascending (and strictly descending) runs
+ *   and only merges those, O(n) on already sorted input
+ */
+
+use std::cmp::Ordering;
+use std::ptr;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::sorting_network::{small_sort_stable, MAX_NETWORK_LEN};
+use super::stats::{counted, record_merge, record_writes, reverse, Scratch};
+
+// ranges at or below this length are sorted with a stable sorting network before merging
+const SMALL_SORT_THRESHOLD: usize = MAX_NETWORK_LEN;
+
+pub fn mergesort<T: PartialOrd>(arr: &mut [T]) {
+    mergesort_by(arr, partial_order);
+}
+
+// stable: equal elements keep their relative order
+pub fn mergesort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let mut buf: Vec<T> = Vec::with_capacity(arr.len() / 2);
+    let _scratch = Scratch::new::<T>(arr.len() / 2);
+    merge_sort_range(arr, buf.as_mut_ptr(), &mut compare);
+}
+
+pub fn mergesort_by_key<T, K, F>(arr: &mut [T], mut key: F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized + Clone,
-    O: PartialOrd + Clone,
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
 {
-    let len = container.as_ref().len();
+    mergesort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
 
-    if len > 1 {
-        let mid = len / 2;
-        let mut left = container.as_ref()[0..mid].to_vec();
-        let mut right = container.as_ref()[mid..len].to_vec();
+pub fn mergesort_bottom_up<T: PartialOrd>(arr: &mut [T]) {
+    mergesort_bottom_up_by(arr, partial_order);
+}
 
-        mergesort(&mut left);
-        mergesort(&mut right);
+pub fn mergesort_bottom_up_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let len = arr.len();
+    let mut buf: Vec<T> = Vec::with_capacity(len / 2);
+    let _scratch = Scratch::new::<T>(len / 2);
 
-        let mut i = 0;
-        let mut j = 0;
-        let mut k = 0;
+    // sort small blocks first, then merge neighbouring blocks with doubling width
+    for block in arr.chunks_mut(SMALL_SORT_THRESHOLD) {
+        small_sort_stable(block, &mut compare);
+    }
 
-        while i < left.len() && j < right.len() {
-            if left[i] < right[j] {
-                container.as_mut()[k] = left[i].clone();
-                i += 1;
+    let mut width = SMALL_SORT_THRESHOLD;
+    while width < len {
+        let mut lo = 0;
+        while lo + width < len {
+            let hi = usize::min(lo + 2 * width, len);
+            merge_if_needed(&mut arr[lo..hi], width, buf.as_mut_ptr(), &mut compare);
+            lo = hi;
+        }
+        width *= 2;
+    }
+}
+
+pub fn mergesort_bottom_up_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    mergesort_bottom_up_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn mergesort_natural<T: PartialOrd>(arr: &mut [T]) {
+    mergesort_natural_by(arr, partial_order);
+}
+
+pub fn mergesort_natural_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let len = arr.len();
+    let mut buf: Vec<T> = Vec::with_capacity(len / 2);
+    let _scratch = Scratch::new::<T>(len / 2);
+
+    // end index of every run, runs cover the whole slice back to back
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let mut end = start + 1;
+        if end < len && compare(&arr[end], &arr[end - 1]) == Ordering::Less {
+            // strictly descending, reversing it can't reorder equal elements
+            while end < len && compare(&arr[end], &arr[end - 1]) == Ordering::Less {
+                end += 1;
+            }
+            reverse(&mut arr[start..end]);
+        } else {
+            while end < len && compare(&arr[end], &arr[end - 1]) != Ordering::Less {
+                end += 1;
+            }
+        }
+        runs.push(end);
+        start = end;
+    }
+
+    // merge neighbouring runs pairwise until a single run is left
+    while runs.len() > 1 {
+        let mut lo = 0;
+        let mut kept = 0;
+        let mut i = 0;
+        while i < runs.len() {
+            if i + 1 < runs.len() {
+                let (mid, hi) = (runs[i], runs[i + 1]);
+                merge_if_needed(&mut arr[lo..hi], mid - lo, buf.as_mut_ptr(), &mut compare);
+                runs[kept] = hi;
+                i += 2;
             } else {
-                container.as_mut()[k] = right[j].clone();
-                j += 1;
+                runs[kept] = runs[i];
+                i += 1;
             }
-            k += 1;
+            lo = runs[kept];
+            kept += 1;
         }
+        runs.truncate(kept);
+    }
+}
+
+pub fn mergesort_natural_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    mergesort_natural_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+fn merge_sort_range<T, F>(arr: &mut [T], buf: *mut T, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    if len <= SMALL_SORT_THRESHOLD {
+        small_sort_stable(arr, compare);
+        return;
+    }
+
+    let mid = len / 2;
+    merge_sort_range(&mut arr[..mid], buf, compare);
+    merge_sort_range(&mut arr[mid..], buf, compare);
+    merge_if_needed(arr, mid, buf, compare);
+}
 
-        while i < left.len() {
-            container.as_mut()[k] = left[i].clone();
-            i += 1;
-            k += 1;
+// merges arr[..mid] and arr[mid..] unless they already happen to be in order
+// buf must have room for min(mid, arr.len() - mid) elements
+pub(super) fn merge_if_needed<T, F>(arr: &mut [T], mid: usize, buf: *mut T, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if mid == 0 || mid == arr.len() || compare(&arr[mid], &arr[mid - 1]) != Ordering::Less {
+        return;
+    }
+    unsafe { merge(arr, mid, buf, compare) }
+}
+
+/*
+    While merging, the run parked in the scratch buffer is (logically) missing from the slice.
+    The hole tracks what is still in the buffer and where it belongs, and its Drop moves it back.
+    That runs both when the merge finishes and when the comparator panics half way,
+    so the slice always ends up holding every element exactly once.
+*/
+struct MergeHole<T> {
+    start: *mut T, // remaining buffered elements are start..end
+    end: *mut T,
+    dest: *mut T,  // gap in the slice the remaining elements belong in
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let remaining = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, remaining);
         }
+    }
+}
+
+/*
+    Stable merge of the sorted runs v[..mid] and v[mid..].
+    The shorter run is moved into buf, then the runs are merged forwards (left run buffered)
+    or backwards (right run buffered) so writes never overtake unread elements.
+    Safety: buf must be valid for min(mid, len - mid) writes and not alias v.
+*/
+unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    record_merge(v, mid);
+    let len = v.len();
+    let v = v.as_mut_ptr();
 
-        while j < right.len() {
-            container.as_mut()[k] = right[j].clone();
-            j += 1;
-            k += 1;
+    if mid <= len - mid {
+        ptr::copy_nonoverlapping(v, buf, mid);
+        let mut hole = MergeHole { start: buf, end: buf.add(mid), dest: v };
+        let mut right = v.add(mid);
+        let right_end = v.add(len);
+
+        while hole.start < hole.end && right < right_end {
+            // take from the right only when strictly smaller, ties keep the left element first
+            let src = if compare(&*right, &*hole.start) == Ordering::Less {
+                let src = right;
+                right = right.add(1);
+                src
+            } else {
+                let src = hole.start;
+                hole.start = hole.start.add(1);
+                src
+            };
+            ptr::copy_nonoverlapping(src, hole.dest, 1);
+            hole.dest = hole.dest.add(1);
         }
+        // into the buffer and back, plus every right element that had to shift
+        record_writes(buf, mid);
+        record_writes(v, mid + right.offset_from(v.add(mid)) as usize);
+        // dropping the hole moves the leftover left run in, leftover right elements are already in place
+    } else {
+        ptr::copy_nonoverlapping(v.add(mid), buf, len - mid);
+        let mut hole = MergeHole { start: buf, end: buf.add(len - mid), dest: v.add(mid) };
+        let mut left = v.add(mid);
+        let mut out = v.add(len);
+
+        while v < left && hole.start < hole.end {
+            // the bigger tail goes last, ties take the right element so it stays behind the left one
+            let src = if compare(&*hole.end.sub(1), &*left.sub(1)) == Ordering::Less {
+                left = left.sub(1);
+                left
+            } else {
+                hole.end = hole.end.sub(1);
+                hole.end
+            };
+            out = out.sub(1);
+            ptr::copy_nonoverlapping(src, out, 1);
+            hole.dest = left;
+        }
+        record_writes(buf, len - mid);
+        record_writes(left, len - left.offset_from(v) as usize);
+        // dropping the hole moves the leftover right run into the gap at left..out
+    }
+}
+
+pub struct MergeSort;
+
+impl<T: PartialOrd> Sorter<T> for MergeSort {
+    fn name(&self) -> &'static str {
+        "Merge sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        mergesort(arr);
+    }
+}
+
+pub struct MergeSortBottomUp;
+
+impl<T: PartialOrd> Sorter<T> for MergeSortBottomUp {
+    fn name(&self) -> &'static str {
+        "Merge sort bottom-up"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        mergesort_bottom_up(arr);
+    }
+}
+
+pub struct MergeSortNatural;
+
+impl<T: PartialOrd> Sorter<T> for MergeSortNatural {
+    fn name(&self) -> &'static str {
+        "Merge sort natural"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        mergesort_natural(arr);
     }
 }
 
@@ -93,4 +345,138 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        mergesort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+
+    // (key, original index) pairs with plenty of duplicate keys
+    fn tagged_input(len: usize) -> Vec<(u8, usize)> {
+        let mut keys: Vec<u8> = (0..len).map(|i| (i % 7) as u8).collect();
+        keys.shuffle(&mut thread_rng());
+        keys.into_iter().enumerate().map(|(i, k)| (k, i)).collect()
+    }
+
+    fn assert_stable(arr: &[(u8, usize)]) {
+        for w in arr.windows(2) {
+            assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1), "{:?}", w);
+        }
+    }
+
+    #[test]
+    fn test_merge_sort_variants() {
+        let mut arr: Vec<_> = (0..1000).collect();
+        arr.shuffle(&mut thread_rng());
+        let sorted: Vec<_> = (0..1000).collect();
+
+        let variants: [fn(&mut [i32]); 3] = [mergesort, mergesort_bottom_up, mergesort_natural];
+        for sort in variants {
+            for len in [0, 1, 2, 15, 16, 17, 100, 1000] {
+                let mut input = arr[..len].to_vec();
+                sort(&mut input);
+                let mut expected = arr[..len].to_vec();
+                expected.sort();
+                assert_eq!(input, expected);
+            }
+            let mut reversed: Vec<_> = sorted.iter().rev().copied().collect();
+            sort(&mut reversed);
+            assert_eq!(reversed, sorted);
+        }
+    }
+
+    #[test]
+    fn test_merge_sort_stable() {
+        let input = tagged_input(2000);
+
+        let mut arr = input.clone();
+        mergesort_by_key(&mut arr, |p| p.0);
+        assert_stable(&arr);
+
+        let mut arr = input.clone();
+        mergesort_bottom_up_by_key(&mut arr, |p| p.0);
+        assert_stable(&arr);
+
+        let mut arr = input;
+        mergesort_natural_by_key(&mut arr, |p| p.0);
+        assert_stable(&arr);
+    }
+
+    #[test]
+    fn test_merge_sort_natural_runs() {
+        // two ascending runs and a strictly descending one
+        let mut arr: Vec<_> = (0..500).chain(250..750).chain((750..1000).rev()).collect();
+        mergesort_natural(&mut arr);
+        let mut expected: Vec<_> = (0..500).chain(250..750).chain(750..1000).collect();
+        expected.sort();
+        assert_eq!(arr, expected);
+
+        // descending with duplicates is not strictly descending, must stay stable
+        let mut pairs: Vec<(u8, usize)> = (0..100).map(|i| ((100 - i) as u8 / 2, i)).collect();
+        mergesort_natural_by_key(&mut pairs, |p| p.0);
+        assert_stable(&pairs);
+    }
+
+    #[test]
+    fn test_merge_sort_moves_without_clone() {
+        // not Clone, and owns heap memory so a double drop would crash under the allocator
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Boxed(Box<u32>);
+
+        let mut keys: Vec<u32> = (0..500).collect();
+        keys.shuffle(&mut thread_rng());
+        let mut arr: Vec<Boxed> = keys.iter().map(|&k| Boxed(Box::new(k))).collect();
+        mergesort(&mut arr);
+        for (i, b) in arr.iter().enumerate() {
+            assert_eq!(*b.0, i as u32);
+        }
+
+        mergesort_by(&mut arr, |a, b| b.0.cmp(&a.0));
+        assert_eq!(*arr[0].0, 499);
+    }
+
+    #[test]
+    fn test_merge_sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        fn run<F: FnMut(&String, &String) -> Ordering>(variant: usize, arr: &mut [String], compare: F) {
+            match variant {
+                0 => mergesort_by(arr, compare),
+                1 => mergesort_bottom_up_by(arr, compare),
+                _ => mergesort_natural_by(arr, compare),
+            }
+        }
+
+        let mut input: Vec<String> = (0..200).map(|i| format!("{:03}", i)).collect();
+        input.shuffle(&mut thread_rng());
+
+        for variant in 0..3 {
+            // count the comparisons of a full run, then panic a few before the end (inside the last merge)
+            let mut total = 0;
+            run(variant, &mut input.clone(), |a, b| {
+                total += 1;
+                a.cmp(b)
+            });
+
+            let mut arr = input.clone();
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                run(variant, &mut arr, |a, b| {
+                    calls += 1;
+                    if calls == total - 5 {
+                        panic!("comparator gave up");
+                    }
+                    a.cmp(b)
+                });
+            }));
+            assert!(result.is_err());
+
+            // every element is still there exactly once
+            arr.sort();
+            let expected: Vec<String> = (0..200).map(|i| format!("{:03}", i)).collect();
+            assert_eq!(arr, expected);
+        }
+    }
 }
\ No newline at end of file
@@ -1,9 +1,56 @@
 pub mod bubble_sort;
+pub mod bucket_sort;
+pub mod cocktail_sort;
+pub mod comb_sort;
+pub mod counting_sort;
+pub mod external_sort;
+pub mod gnome_sort;
 pub mod heap_sort;
+pub mod inputs;
 pub mod insertion_sort;
 pub mod merge_sort;
+pub mod parallel_sort;
+#[cfg(test)]
+mod properties;
 pub mod quick_sort;
+pub mod radix_sort;
+pub mod select;
 pub mod selection_sort;
+pub mod shell_sort;
+pub mod sorter;
+pub mod sorting_network;
+pub mod stats;
+pub mod tim_sort;
+pub mod trace;
 
+use std::cmp::Ordering;
 
+// Ordering used by the plain (non _by) sorts, incomparable values such as NaN compare as equal
+pub(crate) fn partial_order<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
 
+/*
+    Used by the non-comparison sorts: they sort (key, index) pairs and then move every
+    element once. order[i] is the original index of the element that ends up at i.
+    Follows the permutation cycles with swaps, so T needs neither Copy nor Clone.
+*/
+pub(crate) fn apply_order<T, I>(arr: &mut [T], order: I)
+where
+    I: IntoIterator<Item = usize>,
+{
+    // dest[i] = final position of the element currently at i
+    let mut dest = vec![0; arr.len()];
+    let _scratch = stats::Scratch::new::<usize>(dest.len());
+    for (pos, from) in order.into_iter().enumerate() {
+        dest[from] = pos;
+    }
+
+    for i in 0..arr.len() {
+        while dest[i] != i {
+            let d = dest[i];
+            stats::swap(arr, i, d);
+            dest.swap(i, d);
+        }
+    }
+}
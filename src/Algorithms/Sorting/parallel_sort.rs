@@ -0,0 +1,339 @@
+/***
+ * Parallel Sort
+ * Merge sort and quick sort split across std::thread::scope workers
+ *
+ * Both recurse like their sequential versions, but while there are threads left in the
+ * budget one half is handed to a scoped worker and the other half stays on the current thread.
+ * Slices at or below the sequential cutoff, or once the budget is down to one thread,
+ * are finished with the sequential sort (mergesort / introsort).
+ *
+ * Variants:
+ * - par_mergesort: stable, halves are sorted in parallel, the final merges run on the
+ *   joining thread, so the top merge (O(n)) bounds the speedup
+ * - par_quicksort: unstable, the partition runs on the current thread before the sides
+ *   are split off, O(n log n) worst case since the sequential part is introsort
+ *
+ * The comparator is shared between threads, so it has to be Fn + Sync instead of FnMut.
+ * Its calls are counted where they happen: in the splits and merges here and in the
+ * sequential sorts that finish the slices.
+ * A panic in any worker is re-raised by the scope once all workers have stopped,
+ * every element is still in the slice exactly once.
+ */
+
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+use std::thread;
+use super::merge_sort::{merge_if_needed, mergesort_by};
+use super::partial_order;
+use super::quick_sort::{choose_pivot, introsort_by, partition, PivotStrategy};
+use super::sorter::Sorter;
+use super::stats::{counted, Recorder, Scratch};
+
+// default: slices at or below this length are not worth a thread
+const SEQUENTIAL_CUTOFF: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelConfig {
+    pub threads: usize,           // total threads working at once, including the caller
+    pub sequential_cutoff: usize, // slices at or below this length are sorted sequentially
+}
+
+impl ParallelConfig {
+    // threads is clamped to at least 1 (which sorts sequentially)
+    pub fn new(threads: usize, sequential_cutoff: usize) -> Self {
+        ParallelConfig {
+            threads: threads.max(1),
+            sequential_cutoff,
+        }
+    }
+}
+
+impl Default for ParallelConfig {
+    // one thread per core
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        ParallelConfig::new(threads, SEQUENTIAL_CUTOFF)
+    }
+}
+
+pub fn par_mergesort<T: PartialOrd + Send>(arr: &mut [T]) {
+    par_mergesort_with_by(arr, ParallelConfig::default(), partial_order);
+}
+
+pub fn par_mergesort_by<T, F>(arr: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_mergesort_with_by(arr, ParallelConfig::default(), compare);
+}
+
+pub fn par_mergesort_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    T: Send,
+    F: Fn(&T) -> K + Sync,
+    K: PartialOrd,
+{
+    par_mergesort_with_by(arr, ParallelConfig::default(), |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn par_mergesort_with<T: PartialOrd + Send>(arr: &mut [T], config: ParallelConfig) {
+    par_mergesort_with_by(arr, config, partial_order);
+}
+
+pub fn par_mergesort_with_by<T, F>(arr: &mut [T], config: ParallelConfig, compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_merge_sort_range(arr, config.threads, config.sequential_cutoff, &compare);
+}
+
+pub fn par_quicksort<T: PartialOrd + Send>(arr: &mut [T]) {
+    par_quicksort_with_by(arr, ParallelConfig::default(), partial_order);
+}
+
+pub fn par_quicksort_by<T, F>(arr: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quicksort_with_by(arr, ParallelConfig::default(), compare);
+}
+
+pub fn par_quicksort_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    T: Send,
+    F: Fn(&T) -> K + Sync,
+    K: PartialOrd,
+{
+    par_quicksort_with_by(arr, ParallelConfig::default(), |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn par_quicksort_with<T: PartialOrd + Send>(arr: &mut [T], config: ParallelConfig) {
+    par_quicksort_with_by(arr, config, partial_order);
+}
+
+pub fn par_quicksort_with_by<T, F>(arr: &mut [T], config: ParallelConfig, compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quick_sort_range(arr, config.threads, config.sequential_cutoff, &compare);
+}
+
+fn par_merge_sort_range<T, F>(arr: &mut [T], threads: usize, cutoff: usize, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let len = arr.len();
+    if threads <= 1 || len <= cutoff.max(1) {
+        mergesort_by(arr, compare);
+        return;
+    }
+
+    let mid = len / 2;
+    let (left, right) = arr.split_at_mut(mid);
+    let left_threads = threads / 2;
+    // workers count into the same sort stats as the caller
+    let recorder = Recorder::current();
+    thread::scope(|s| {
+        s.spawn(|| recorder.run(|| par_merge_sort_range(left, left_threads, cutoff, compare)));
+        par_merge_sort_range(right, threads - left_threads, cutoff, compare);
+    });
+
+    let mut buf: Vec<T> = Vec::with_capacity(len - mid);
+    let _scratch = Scratch::new::<T>(len - mid);
+    merge_if_needed(arr, mid, buf.as_mut_ptr(), &mut counted(|a: &T, b: &T| compare(a, b)));
+}
+
+fn par_quick_sort_range<T, F>(arr: &mut [T], threads: usize, cutoff: usize, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if threads <= 1 || arr.len() <= cutoff.max(1) {
+        introsort_by(arr, compare);
+        return;
+    }
+
+    let mut compare_mut = counted(|a: &T, b: &T| compare(a, b));
+    let pivot = choose_pivot(arr, PivotStrategy::Ninther, &mut compare_mut);
+    let pivot = partition(arr, pivot, &mut compare_mut);
+    let (left, rest) = arr.split_at_mut(pivot);
+    let right = &mut rest[1..];
+
+    // give each side a share of the threads that matches its size
+    let left_threads = (threads * left.len() / (left.len() + right.len()).max(1)).clamp(1, threads - 1);
+    let recorder = Recorder::current();
+    thread::scope(|s| {
+        s.spawn(|| recorder.run(|| par_quick_sort_range(left, left_threads, cutoff, compare)));
+        par_quick_sort_range(right, threads - left_threads, cutoff, compare);
+    });
+}
+
+pub struct ParallelMergeSort;
+
+impl<T: PartialOrd + Send> Sorter<T> for ParallelMergeSort {
+    fn name(&self) -> &'static str {
+        "Parallel merge sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        par_mergesort(arr);
+    }
+}
+
+pub struct ParallelQuickSort;
+
+impl<T: PartialOrd + Send> Sorter<T> for ParallelQuickSort {
+    fn name(&self) -> &'static str {
+        "Parallel quick sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        par_quicksort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    // small cutoff so even the test inputs get split across several threads
+    fn configs() -> Vec<ParallelConfig> {
+        vec![
+            ParallelConfig::new(1, 16),
+            ParallelConfig::new(2, 16),
+            ParallelConfig::new(3, 64),
+            ParallelConfig::new(8, 16),
+            ParallelConfig::default(),
+        ]
+    }
+
+    #[test]
+    fn test_parallel_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        par_mergesort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut arr = [5, 4, 3, 2, 1];
+        par_quicksort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        par_mergesort_with(&mut empty, ParallelConfig::new(4, 0));
+        par_quicksort_with(&mut empty, ParallelConfig::new(4, 0));
+    }
+
+    #[test]
+    fn test_parallel_sort_configs() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<i32> = (0..5000).map(|_| rng.gen_range(-1000..1000)).collect();
+        arr.extend(0..3000);
+        arr.shuffle(&mut rng);
+        let mut expected = arr.clone();
+        expected.sort();
+
+        for config in configs() {
+            let mut merged = arr.clone();
+            par_mergesort_with(&mut merged, config);
+            assert_eq!(merged, expected, "{:?}", config);
+
+            let mut quick = arr.clone();
+            par_quicksort_with(&mut quick, config);
+            assert_eq!(quick, expected, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn test_parallel_sort_sorted_and_equal_input() {
+        for config in configs() {
+            let mut sorted: Vec<u32> = (0..4000).collect();
+            par_quicksort_with(&mut sorted, config);
+            assert_eq!(sorted, (0..4000).collect::<Vec<_>>());
+
+            let mut equal = vec![7u8; 4000];
+            par_quicksort_with(&mut equal, config);
+            par_mergesort_with(&mut equal, config);
+            assert!(equal.iter().all(|&x| x == 7));
+        }
+    }
+
+    #[test]
+    fn test_parallel_merge_sort_stable() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<(u8, usize)> = (0..5000).map(|i| (rng.gen_range(0..10), i)).collect();
+        par_mergesort_with_by(&mut arr, ParallelConfig::new(4, 32), |a, b| a.0.cmp(&b.0));
+        for w in arr.windows(2) {
+            assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1), "{:?}", w);
+        }
+    }
+
+    #[test]
+    fn test_parallel_sort_by_key() {
+        let mut arr: Vec<String> = (0..3000).map(|i| i.to_string()).collect();
+        arr.shuffle(&mut thread_rng());
+        let mut quick = arr.clone();
+
+        par_mergesort_by_key(&mut arr, |s| s.parse::<u32>().unwrap());
+        par_quicksort_by(&mut quick, |a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        let expected: Vec<String> = (0..3000).map(|i| i.to_string()).collect();
+        assert_eq!(arr, expected);
+        assert_eq!(quick, expected);
+    }
+
+    #[test]
+    fn test_parallel_sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        let mut input: Vec<String> = (0..3000).map(|i| format!("{:04}", i)).collect();
+        input.shuffle(&mut thread_rng());
+
+        for quick in [false, true] {
+            let mut arr = input.clone();
+            let calls = AtomicUsize::new(0);
+            let compare = |a: &String, b: &String| {
+                if calls.fetch_add(1, AtomicOrdering::Relaxed) == 20000 {
+                    panic!("comparator gave up");
+                }
+                a.cmp(b)
+            };
+            let config = ParallelConfig::new(4, 64);
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                if quick {
+                    par_quicksort_with_by(&mut arr, config, compare);
+                } else {
+                    par_mergesort_with_by(&mut arr, config, compare);
+                }
+            }));
+            assert!(result.is_err());
+
+            // every element is still there exactly once
+            arr.sort();
+            let expected: Vec<String> = (0..3000).map(|i| format!("{:04}", i)).collect();
+            assert_eq!(arr, expected);
+        }
+    }
+
+    // test 1000000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_parallel_sort_1000000() {
+        let mut arr: Vec<_> = (0..1000000).collect();
+        arr.shuffle(&mut thread_rng());
+        let mut quick = arr.clone();
+        par_mergesort(&mut arr);
+        par_quicksort(&mut quick);
+        assert_eq!(arr, (0..1000000).collect::<Vec<_>>());
+        assert_eq!(quick, arr);
+    }
+}
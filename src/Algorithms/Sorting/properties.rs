@@ -0,0 +1,297 @@
+/***
+ * Properties
+ * Correctness checks shared by every sort in the module, test builds only
+ *
+ * Each sort runs on every inputs::Pattern at a spread of lengths (0, 1, the sorting
+ * network boundary, a few hundred) with elements tagged by their original index.
+ * Comparisons only look at the key, so the tags show exactly where every element went:
+ *
+ * - permutation: every input element comes out exactly once, unchanged, keys ascending
+ * - stability: equal keys keep their input order, checked for the sorts that promise it
+ * - empty and single element input: nothing to do, nothing may go wrong
+ * - panic safety: a comparator that panics after k comparisons leaves every element in
+ *   the slice exactly once, and each one is dropped exactly once afterwards
+ *
+ * A new sort only needs an entry in comparison_sorts() or key_sorts(), and its _by_key
+ * wrapper one in key_wrappers().
+ */
+
+use std::cmp::Ordering;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use super::bubble_sort::{bubblesort_by, bubblesort_by_key};
+use super::bucket_sort::bucket_sort_by_key;
+use super::cocktail_sort::{cocktailsort_by, cocktailsort_by_key};
+use super::comb_sort::{combsort_by, combsort_by_key};
+use super::counting_sort::counting_sort_by_key;
+use super::gnome_sort::{gnomesort_by, gnomesort_by_key};
+use super::heap_sort::{heapsort_by, heapsort_by_key};
+use super::inputs::Pattern;
+use super::insertion_sort::{insertionsort_by, insertionsort_by_key};
+use super::merge_sort::{
+    mergesort_bottom_up_by, mergesort_bottom_up_by_key, mergesort_by, mergesort_by_key, mergesort_natural_by,
+    mergesort_natural_by_key,
+};
+use super::parallel_sort::{
+    par_mergesort_by_key, par_mergesort_with_by, par_quicksort_by_key, par_quicksort_with_by, ParallelConfig,
+    ParallelMergeSort, ParallelQuickSort,
+};
+use super::quick_sort::{
+    introsort_by, introsort_by_key, quicksort_by, quicksort_by_key, quicksort_three_way_by, quicksort_with_by,
+    PivotStrategy,
+};
+use super::radix_sort::{radix_sort_by_key, radix_sort_bytes_by_key, radix_sort_msd_by_key};
+use super::select::{partial_sort_by, partial_sort_by_key};
+use super::selection_sort::{selectionsort_by, selectionsort_by_key};
+use super::shell_sort::{shellsort_by, shellsort_by_key};
+use super::sorter::all_integer_sorters;
+use super::sorting_network::{network_sort_by, network_sort_by_key};
+use super::tim_sort::{timsort_by, timsort_by_key};
+
+type Compare<'a, T> = &'a (dyn Fn(&T, &T) -> Ordering + Sync);
+type SortBy<T> = fn(&mut [T], Compare<T>);
+type SortByKey<T> = fn(&mut [T], fn(&T) -> i32);
+
+const LENGTHS: [usize; 10] = [0, 1, 2, 3, 15, 16, 17, 64, 100, 300];
+
+// small enough that the parallel sorts actually split at the test lengths
+const PARALLEL: ParallelConfig = ParallelConfig { threads: 4, sequential_cutoff: 32 };
+
+struct Algorithm<S> {
+    name: &'static str,
+    stable: bool,
+    sort: S,
+}
+
+// every comparator driven sort in the module
+fn comparison_sorts<T: Send>() -> Vec<Algorithm<SortBy<T>>> {
+    let algorithm = |name, stable, sort: SortBy<T>| Algorithm { name, stable, sort };
+    vec![
+        algorithm("bubblesort", true, |arr, compare| bubblesort_by(arr, compare)),
+        algorithm("insertionsort", true, |arr, compare| insertionsort_by(arr, compare)),
+        algorithm("selectionsort", false, |arr, compare| selectionsort_by(arr, compare)),
+        algorithm("cocktailsort", true, |arr, compare| cocktailsort_by(arr, compare)),
+        algorithm("gnomesort", true, |arr, compare| gnomesort_by(arr, compare)),
+        algorithm("combsort", false, |arr, compare| combsort_by(arr, compare)),
+        algorithm("shellsort", false, |arr, compare| shellsort_by(arr, compare)),
+        algorithm("mergesort", true, |arr, compare| mergesort_by(arr, compare)),
+        algorithm("mergesort_bottom_up", true, |arr, compare| mergesort_bottom_up_by(arr, compare)),
+        algorithm("mergesort_natural", true, |arr, compare| mergesort_natural_by(arr, compare)),
+        algorithm("timsort", true, |arr, compare| timsort_by(arr, compare)),
+        algorithm("quicksort", false, |arr, compare| quicksort_by(arr, compare)),
+        algorithm("quicksort last pivot", false, |arr, compare| {
+            quicksort_with_by(arr, PivotStrategy::Last, compare)
+        }),
+        algorithm("quicksort three way", false, |arr, compare| {
+            quicksort_three_way_by(arr, PivotStrategy::Ninther, compare)
+        }),
+        algorithm("introsort", false, |arr, compare| introsort_by(arr, compare)),
+        algorithm("heapsort", false, |arr, compare| heapsort_by(arr, compare)),
+        algorithm("network_sort", false, |arr, compare| network_sort_by(arr, compare)),
+        algorithm("partial_sort", false, |arr, compare| partial_sort_by(arr, arr.len(), compare)),
+        algorithm("par_mergesort", true, |arr, compare| par_mergesort_with_by(arr, PARALLEL, compare)),
+        algorithm("par_quicksort", false, |arr, compare| par_quicksort_with_by(arr, PARALLEL, compare)),
+    ]
+}
+
+// the sorts that only see keys, all of them stable
+fn key_sorts<T>() -> Vec<Algorithm<SortByKey<T>>> {
+    let algorithm = |name, sort: SortByKey<T>| Algorithm { name, stable: true, sort };
+    vec![
+        algorithm("counting_sort", |arr, key| counting_sort_by_key(arr, key)),
+        algorithm("radix_sort", |arr, key| radix_sort_by_key(arr, key)),
+        algorithm("radix_sort_msd", |arr, key| radix_sort_msd_by_key(arr, key)),
+        algorithm("radix_sort_bytes", |arr, key| radix_sort_bytes_by_key(arr, |x| key(x).to_be_bytes())),
+        algorithm("bucket_sort", |arr, key| {
+            let max = arr.len() as f64;
+            bucket_sort_by_key(arr, 0.0, max, |x| key(x) as f64)
+        }),
+    ]
+}
+
+// the _by_key entry points of the comparison sorts, each a thin wrapper around its _by
+fn key_wrappers<T: Send>() -> Vec<Algorithm<SortByKey<T>>> {
+    let algorithm = |name, stable, sort: SortByKey<T>| Algorithm { name, stable, sort };
+    vec![
+        algorithm("bubblesort_by_key", true, |arr, key| bubblesort_by_key(arr, key)),
+        algorithm("insertionsort_by_key", true, |arr, key| insertionsort_by_key(arr, key)),
+        algorithm("selectionsort_by_key", false, |arr, key| selectionsort_by_key(arr, key)),
+        algorithm("cocktailsort_by_key", true, |arr, key| cocktailsort_by_key(arr, key)),
+        algorithm("gnomesort_by_key", true, |arr, key| gnomesort_by_key(arr, key)),
+        algorithm("combsort_by_key", false, |arr, key| combsort_by_key(arr, key)),
+        algorithm("shellsort_by_key", false, |arr, key| shellsort_by_key(arr, key)),
+        algorithm("mergesort_by_key", true, |arr, key| mergesort_by_key(arr, key)),
+        algorithm("mergesort_bottom_up_by_key", true, |arr, key| mergesort_bottom_up_by_key(arr, key)),
+        algorithm("mergesort_natural_by_key", true, |arr, key| mergesort_natural_by_key(arr, key)),
+        algorithm("timsort_by_key", true, |arr, key| timsort_by_key(arr, key)),
+        algorithm("quicksort_by_key", false, |arr, key| quicksort_by_key(arr, key)),
+        algorithm("introsort_by_key", false, |arr, key| introsort_by_key(arr, key)),
+        algorithm("heapsort_by_key", false, |arr, key| heapsort_by_key(arr, key)),
+        algorithm("network_sort_by_key", false, |arr, key| network_sort_by_key(arr, key)),
+        algorithm("partial_sort_by_key", false, |arr, key| partial_sort_by_key(arr, arr.len(), key)),
+        algorithm("par_mergesort_by_key", true, |arr, key| par_mergesort_by_key(arr, key)),
+        algorithm("par_quicksort_by_key", false, |arr, key| par_quicksort_by_key(arr, key)),
+    ]
+}
+
+#[derive(Debug)]
+struct Tagged<'a> {
+    key: i32,
+    tag: usize, // index in the input
+    drops: Option<&'a AtomicUsize>,
+}
+
+impl Drop for Tagged<'_> {
+    fn drop(&mut self) {
+        if let Some(drops) = self.drops {
+            drops.fetch_add(1, AtomicOrdering::SeqCst);
+        }
+    }
+}
+
+fn tagged<'a>(keys: &[i32], drops: Option<&'a AtomicUsize>) -> Vec<Tagged<'a>> {
+    keys.iter().enumerate().map(|(tag, &key)| Tagged { key, tag, drops }).collect()
+}
+
+fn by_key(a: &Tagged, b: &Tagged) -> Ordering {
+    a.key.cmp(&b.key)
+}
+
+// every input element exactly once and unchanged, the keys ascending and, if stable, equal keys in input order
+fn check_sorted(name: &str, keys: &[i32], arr: &[Tagged], stable: bool) {
+    assert_eq!(arr.len(), keys.len(), "{}: length changed", name);
+    let mut seen = vec![false; keys.len()];
+    for x in arr {
+        assert!(!seen[x.tag], "{}: element {} came out twice", name, x.tag);
+        seen[x.tag] = true;
+        assert_eq!(x.key, keys[x.tag], "{}: element {} was changed", name, x.tag);
+    }
+    for (i, pair) in arr.windows(2).enumerate() {
+        let (a, b) = (&pair[0], &pair[1]);
+        assert!(a.key <= b.key, "{}: out of order at {}, {} > {}", name, i, a.key, b.key);
+        if stable && a.key == b.key {
+            assert!(a.tag < b.tag, "{}: not stable at {}, key {}", name, i, a.key);
+        }
+    }
+}
+
+fn for_each_input(mut check: impl FnMut(String, &[i32])) {
+    for pattern in Pattern::ALL {
+        for len in LENGTHS {
+            check(format!("{} {}", pattern.name(), len), &pattern.generate(len));
+        }
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparison_sorts_permute_and_order() {
+        for algorithm in comparison_sorts() {
+            for_each_input(|input, keys| {
+                let mut arr = tagged(keys, None);
+                (algorithm.sort)(&mut arr, &by_key);
+                check_sorted(&format!("{} on {}", algorithm.name, input), keys, &arr, false);
+            });
+        }
+    }
+
+    #[test]
+    fn key_sorts_permute_and_order() {
+        for algorithm in key_sorts() {
+            for_each_input(|input, keys| {
+                let mut arr = tagged(keys, None);
+                (algorithm.sort)(&mut arr, |x| x.key);
+                check_sorted(&format!("{} on {}", algorithm.name, input), keys, &arr, false);
+            });
+        }
+    }
+
+    #[test]
+    fn key_wrappers_sort_by_the_key() {
+        // stable ones have to stay stable through the wrapper
+        for algorithm in key_wrappers() {
+            for_each_input(|input, keys| {
+                let mut arr = tagged(keys, None);
+                (algorithm.sort)(&mut arr, |x| x.key);
+                check_sorted(&format!("{} on {}", algorithm.name, input), keys, &arr, algorithm.stable);
+            });
+        }
+    }
+
+    #[test]
+    fn stable_sorts_are_stable() {
+        // few unique and many duplicates are where stability shows, but every pattern has to pass
+        for algorithm in comparison_sorts().into_iter().filter(|a| a.stable) {
+            for_each_input(|input, keys| {
+                let mut arr = tagged(keys, None);
+                (algorithm.sort)(&mut arr, &by_key);
+                check_sorted(&format!("{} on {}", algorithm.name, input), keys, &arr, true);
+            });
+        }
+        for algorithm in key_sorts() {
+            for_each_input(|input, keys| {
+                let mut arr = tagged(keys, None);
+                (algorithm.sort)(&mut arr, |x| x.key);
+                check_sorted(&format!("{} on {}", algorithm.name, input), keys, &arr, true);
+            });
+        }
+    }
+
+    #[test]
+    fn empty_and_single() {
+        let mut sorters = all_integer_sorters::<i32>();
+        sorters.push(Box::new(ParallelMergeSort));
+        sorters.push(Box::new(ParallelQuickSort));
+
+        for sorter in sorters {
+            let mut empty: [i32; 0] = [];
+            sorter.sort(&mut empty);
+            let mut single = [7];
+            sorter.sort(&mut single);
+            assert_eq!(single, [7], "{}", sorter.name());
+            assert_eq!(sorter.sort_with_stats(&mut empty).comparisons, 0, "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn panicking_comparator_keeps_every_element() {
+        let drops = AtomicUsize::new(0);
+        for algorithm in comparison_sorts() {
+            for len in [17, 100, 300] {
+                for budget in [0, 1, 10, 100, 1000] {
+                    let name = format!("{} len {} panic after {}", algorithm.name, len, budget);
+                    let keys = Pattern::Random.generate(len);
+                    drops.store(0, AtomicOrdering::SeqCst);
+                    let mut arr = tagged(&keys, Some(&drops));
+
+                    let left = AtomicUsize::new(budget);
+                    let compare = |a: &Tagged, b: &Tagged| {
+                        if left.fetch_sub(1, AtomicOrdering::SeqCst) == 0 {
+                            panic!("comparator gave up");
+                        }
+                        by_key(a, b)
+                    };
+                    let result = catch_unwind(AssertUnwindSafe(|| (algorithm.sort)(&mut arr, &compare)));
+
+                    // nothing got dropped while unwinding, nothing got duplicated or lost
+                    assert_eq!(drops.load(AtomicOrdering::SeqCst), 0, "{}: dropped during the sort", name);
+                    let mut tags: Vec<usize> = arr.iter().map(|x| x.tag).collect();
+                    tags.sort();
+                    assert_eq!(tags, (0..len).collect::<Vec<_>>(), "{}: not a permutation", name);
+                    if result.is_ok() {
+                        check_sorted(&name, &keys, &arr, false);
+                    }
+
+                    drop(arr);
+                    assert_eq!(drops.load(AtomicOrdering::SeqCst), len, "{}: dropped twice", name);
+                }
+            }
+        }
+    }
+}
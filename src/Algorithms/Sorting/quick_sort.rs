@@ -1,31 +1,315 @@
+/***
+ * Quick Sort
+ * In place, unstable, O(n log n) average, O(n^2) worst case
+ *
+ * Variants:
+ * - quicksort_with: two way (Hoare style) partition around a selectable pivot
+ * - quicksort_three_way: Dutch flag partition, all keys equal to the pivot are done in one pass
+ * - introsort: three way quicksort that falls back to heapsort past a 2*floor(log2(n)) depth
+ *   limit, O(n log n) worst case
+ *
+ * Ranges of up to 16 elements are finished by a sorting network in every variant.
+ *
+ * All variants recurse into the smaller side and loop on the larger one,
+ * so the stack depth stays O(log n) even when partitions are lopsided.
+ */
 
-pub fn quicksort<T: Ord>(arr: &mut [T]) {
-    _quicksort(arr, 0, (arr.len() - 1) as isize);
+use std::cmp::Ordering;
+use rand::Rng;
+use super::heap_sort::heap_sort_range;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::sorting_network::{small_sort, MAX_NETWORK_LEN};
+use super::stats::{counted, record_partition, swap};
+
+// ranges at or below this length are finished by a sorting network
+const SMALL_SORT_THRESHOLD: usize = MAX_NETWORK_LEN;
+// below this length a ninther is not worth it, median of three is used instead
+const NINTHER_THRESHOLD: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotStrategy {
+    Last,          // classic textbook choice, quadratic on sorted input
+    Random,        // uniformly random index
+    MedianOfThree, // median of first, middle and last
+    Ninther,       // Tukey's median of three medians of three
+}
+
+pub fn quicksort<T: PartialOrd>(arr: &mut [T]) {
+    quicksort_by(arr, partial_order);
 }
 
-fn _quicksort<T: Ord>(arr: &mut [T], left: isize, right: isize) {
-    if left <= right {
-        let partition_idx = partition(arr, 0, right);
+pub fn quicksort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quicksort_with_by(arr, PivotStrategy::MedianOfThree, compare);
+}
+
+pub fn quicksort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    quicksort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn quicksort_with<T: PartialOrd>(arr: &mut [T], strategy: PivotStrategy) {
+    quicksort_with_by(arr, strategy, partial_order);
+}
+
+pub fn quicksort_with_by<T, F>(arr: &mut [T], strategy: PivotStrategy, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    quick_sort_range(arr, strategy, &mut compare);
+}
+
+pub fn quicksort_three_way<T: PartialOrd>(arr: &mut [T], strategy: PivotStrategy) {
+    quicksort_three_way_by(arr, strategy, partial_order);
+}
 
-        _quicksort(arr, left, partition_idx - 1);
-        _quicksort(arr, partition_idx + 1, right);
+pub fn quicksort_three_way_by<T, F>(arr: &mut [T], strategy: PivotStrategy, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    three_way_range(arr, strategy, &mut compare);
+}
+
+pub fn introsort<T: PartialOrd>(arr: &mut [T]) {
+    introsort_by(arr, partial_order);
+}
+
+pub fn introsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    intro_sort_range(arr, depth_limit(arr.len()), &mut compare);
+}
+
+pub fn introsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    introsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+fn quick_sort_range<T, F>(mut arr: &mut [T], strategy: PivotStrategy, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while arr.len() > SMALL_SORT_THRESHOLD {
+        let pivot = choose_pivot(arr, strategy, compare);
+        let mid = partition(arr, pivot, compare);
+        let (left, right) = std::mem::take(&mut arr).split_at_mut(mid);
+        let right = &mut right[1..];
+        // recurse into the smaller half, keep looping on the bigger one
+        if left.len() < right.len() {
+            quick_sort_range(left, strategy, compare);
+            arr = right;
+        } else {
+            quick_sort_range(right, strategy, compare);
+            arr = left;
+        }
     }
+    small_sort(arr, compare);
 }
 
-fn partition<T: Ord>(arr: &mut [T], left: isize, right: isize) -> isize {
-    let pivot = right;
-    let mut i: isize = left as isize - 1;
+fn three_way_range<T, F>(mut arr: &mut [T], strategy: PivotStrategy, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while arr.len() > SMALL_SORT_THRESHOLD {
+        let pivot = choose_pivot(arr, strategy, compare);
+        let (lt, gt) = partition_three_way(arr, pivot, compare);
+        let (left, rest) = std::mem::take(&mut arr).split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            three_way_range(left, strategy, compare);
+            arr = right;
+        } else {
+            three_way_range(right, strategy, compare);
+            arr = left;
+        }
+    }
+    small_sort(arr, compare);
+}
 
-    for j in left..=right - 1 {
-        if arr[j as usize] <= arr[pivot as usize] {
+// 2 * floor(log2(n)), the usual introsort budget, also used by introselect
+pub(super) fn depth_limit(len: usize) -> usize {
+    2 * len.checked_ilog2().unwrap_or(0) as usize
+}
+
+fn intro_sort_range<T, F>(mut arr: &mut [T], mut depth: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if arr.len() <= SMALL_SORT_THRESHOLD {
+            small_sort(arr, compare);
+            return;
+        }
+        if depth == 0 {
+            // partitioning keeps going badly, heapsort bounds the rest to O(n log n)
+            heap_sort_range(arr, compare);
+            return;
+        }
+        depth -= 1;
+
+        let pivot = choose_pivot(arr, PivotStrategy::Ninther, compare);
+        let (lt, gt) = partition_three_way(arr, pivot, compare);
+        let (left, rest) = std::mem::take(&mut arr).split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            intro_sort_range(left, depth, compare);
+            arr = right;
+        } else {
+            intro_sort_range(right, depth, compare);
+            arr = left;
+        }
+    }
+}
+
+// precondition: arr is not empty
+pub(super) fn choose_pivot<T, F>(arr: &[T], strategy: PivotStrategy, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    let mid = len / 2;
+    match strategy {
+        PivotStrategy::Last => len - 1,
+        PivotStrategy::Random => rand::thread_rng().gen_range(0..len),
+        PivotStrategy::MedianOfThree => median_of_three(arr, 0, mid, len - 1, compare),
+        PivotStrategy::Ninther if len < NINTHER_THRESHOLD => {
+            median_of_three(arr, 0, mid, len - 1, compare)
+        }
+        PivotStrategy::Ninther => {
+            let step = len / 8;
+            let a = median_of_three(arr, 0, step, 2 * step, compare);
+            let b = median_of_three(arr, mid - step, mid, mid + step, compare);
+            let c = median_of_three(arr, len - 1 - 2 * step, len - 1 - step, len - 1, compare);
+            median_of_three(arr, a, b, c, compare)
+        }
+    }
+}
+
+// index of the median of arr[a], arr[b], arr[c]
+fn median_of_three<T, F>(arr: &[T], a: usize, b: usize, c: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let less = |x: usize, y: usize, compare: &mut F| compare(&arr[x], &arr[y]) == Ordering::Less;
+    if less(a, b, compare) {
+        if less(b, c, compare) {
+            b
+        } else if less(a, c, compare) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c, compare) {
+        a
+    } else if less(b, c, compare) {
+        c
+    } else {
+        b
+    }
+}
+
+/*
+    Two way partition, pivot is parked at index 0 while scanning.
+    i walks right over elements < pivot, j walks left over elements > pivot,
+    elements equal to the pivot get swapped across so duplicates split evenly.
+    Returns the final index of the pivot.
+*/
+pub(super) fn partition<T, F>(arr: &mut [T], pivot: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    swap(arr, 0, pivot);
+    let mut i = 1;
+    let mut j = arr.len() - 1;
+
+    loop {
+        while i <= j && compare(&arr[i], &arr[0]) == Ordering::Less {
             i += 1;
-            arr.swap(i as usize, j as usize);
         }
+        while i <= j && compare(&arr[j], &arr[0]) == Ordering::Greater {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        swap(arr, i, j);
+        i += 1;
+        j -= 1;
     }
 
-    arr.swap((i + 1) as usize, pivot as usize);
+    swap(arr, 0, j);
+    record_partition(arr, j, j + 1);
+    j
+}
+
+/*
+    Dutch national flag partition (Dijkstra), pivot starts at index 0.
+    After the call arr[..lt] < pivot, arr[lt..gt] == pivot, arr[gt..] > pivot.
+    arr[lt] always holds a pivot-equal element, so it doubles as the pivot while scanning.
+*/
+pub(super) fn partition_three_way<T, F>(arr: &mut [T], pivot: usize, compare: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    swap(arr, 0, pivot);
+    let mut lt = 0;
+    let mut i = 1;
+    let mut gt = arr.len();
 
-    i + 1
+    while i < gt {
+        match compare(&arr[i], &arr[lt]) {
+            Ordering::Less => {
+                swap(arr, lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                swap(arr, i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+
+    record_partition(arr, lt, gt);
+    (lt, gt)
+}
+
+pub struct QuickSort;
+
+impl<T: PartialOrd> Sorter<T> for QuickSort {
+    fn name(&self) -> &'static str {
+        "Quick sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        quicksort(arr);
+    }
+}
+
+pub struct IntroSort;
+
+impl<T: PartialOrd> Sorter<T> for IntroSort {
+    fn name(&self) -> &'static str {
+        "Intro sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        introsort(arr);
+    }
 }
 
 // **********  TESTS ********** //
@@ -52,4 +336,112 @@ mod tests {
             assert!(arr[i] <= arr[i + 1]);
         }
     }
+
+    #[test]
+    fn test_quick_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        quicksort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+
+    const STRATEGIES: [PivotStrategy; 4] = [
+        PivotStrategy::Last,
+        PivotStrategy::Random,
+        PivotStrategy::MedianOfThree,
+        PivotStrategy::Ninther,
+    ];
+
+    fn assert_sorted<T: PartialOrd + std::fmt::Debug>(arr: &[T]) {
+        for i in 1..arr.len() {
+            assert!(arr[i - 1] <= arr[i], "{:?} > {:?} at {}", arr[i - 1], arr[i], i);
+        }
+    }
+
+    #[test]
+    fn test_quick_sort_empty_and_single() {
+        let mut empty: Vec<i32> = vec![];
+        quicksort(&mut empty);
+        introsort(&mut empty);
+        quicksort_three_way(&mut empty, PivotStrategy::Ninther);
+        assert!(empty.is_empty());
+
+        let mut single = vec![7];
+        quicksort(&mut single);
+        introsort(&mut single);
+        assert_eq!(single, vec![7]);
+    }
+
+    #[test]
+    fn test_quick_sort_strategies() {
+        let mut shuffled: Vec<_> = (0..1000).collect();
+        shuffled.shuffle(&mut thread_rng());
+        let sorted: Vec<_> = (0..1000).collect();
+        let reversed: Vec<_> = (0..1000).rev().collect();
+
+        for strategy in STRATEGIES {
+            for input in [&shuffled, &sorted, &reversed] {
+                let mut arr = input.clone();
+                quicksort_with(&mut arr, strategy);
+                assert_eq!(arr, sorted, "{:?}", strategy);
+
+                let mut arr = input.clone();
+                quicksort_three_way(&mut arr, strategy);
+                assert_eq!(arr, sorted, "{:?} three way", strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_quick_sort_duplicates() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<u8> = (0..5000).map(|i| (i % 3) as u8).collect();
+        arr.shuffle(&mut rng);
+
+        let mut two_way = arr.clone();
+        quicksort(&mut two_way);
+        assert_sorted(&two_way);
+
+        for strategy in STRATEGIES {
+            let mut three_way = arr.clone();
+            quicksort_three_way(&mut three_way, strategy);
+            assert_eq!(three_way, two_way);
+        }
+
+        // all equal is the worst case for a naive Lomuto partition
+        let mut equal = vec![1; 10000];
+        quicksort(&mut equal);
+        introsort(&mut equal);
+        assert_eq!(equal, vec![1; 10000]);
+    }
+
+    #[test]
+    fn test_intro_sort() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<_> = (0..10000).collect();
+        arr.shuffle(&mut rng);
+        introsort(&mut arr);
+        assert_eq!(arr, (0..10000).collect::<Vec<_>>());
+
+        // sawtooth and organ pipe shapes, the classic quicksort troublemakers
+        let mut sawtooth: Vec<_> = (0..10000).map(|i| i % 37).collect();
+        introsort(&mut sawtooth);
+        assert_sorted(&sawtooth);
+
+        let mut organ: Vec<_> = (0..5000).chain((0..5000).rev()).collect();
+        introsort_by(&mut organ, |a, b| b.cmp(a));
+        assert!(organ.windows(2).all(|w| w[0] >= w[1]));
+
+        let mut people = vec![("cecilia", 41), ("adam", 29), ("bertil", 35), ("doris", 18)];
+        introsort_by_key(&mut people, |p| p.1);
+        assert_eq!(people, vec![("doris", 18), ("adam", 29), ("bertil", 35), ("cecilia", 41)]);
+    }
+
+    #[test]
+    fn test_intro_sort_heapsort_fallback() {
+        // a depth budget of zero sends everything straight to heapsort
+        let mut arr: Vec<_> = (0..1000).rev().collect();
+        intro_sort_range(&mut arr, 0, &mut partial_order);
+        assert_eq!(arr, (0..1000).collect::<Vec<_>>());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,473 @@
+/***
+ * Radix Sort
+ * Non-comparison sort for integers and byte strings, O(w * n) where w is the key width in bytes
+ *
+ * Keys are split into bytes (256 buckets per pass). Signed integers get their sign bit
+ * flipped first (RadixKey::to_radix) so negative values order before positive ones.
+ *
+ * Variants:
+ * - radix_sort: LSD, one stable counting pass per byte from least to most significant.
+ *   Passes where every key shares the same byte are skipped.
+ * - radix_sort_msd: MSD, buckets by the most significant byte and recurses into each bucket,
+ *   small buckets are finished with insertion sort
+ * - radix_sort_bytes: MSD over byte strings (&[u8], String, Vec<u8> ...) of any length,
+ *   a shorter string sorts before every string it is a prefix of. Goes one byte deeper per
+ *   bucket pass, from an explicit stack, so long shared prefixes cost time but no call stack
+ *
+ * The *_by_key variants compute every key once, sort (key, index) pairs and then
+ * move each element into place, so T doesn't need Copy. All variants are stable.
+ *
+ * Upsides:
+ * - Linear time, beats every comparison sort on large integer inputs
+ *
+ * Downsides:
+ * - O(n) extra memory
+ * - Only works on keys that can be split into digits, no custom comparator
+ */
+
+use super::apply_order;
+use super::insertion_sort::insertion_sort_range;
+use super::sorter::Sorter;
+use super::stats::{record_writes, Scratch};
+
+// buckets at or below this length are finished with insertion sort by the MSD variants
+const MSD_INSERTION_THRESHOLD: usize = 32;
+
+// integer keys radix sort can work with
+pub trait RadixKey: Copy {
+    // number of significant bytes, one LSD pass each
+    const BYTES: usize;
+
+    // order preserving map into u64
+    fn to_radix(self) -> u64;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn to_radix(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+// flipping the sign bit maps MIN..=MAX onto 0..=uMAX in order
+macro_rules! impl_radix_key_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn to_radix(self) -> u64 {
+                    ((self as $u) ^ (1 << (<$u>::BITS - 1))) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+pub fn radix_sort<T: RadixKey>(arr: &mut [T]) {
+    lsd(arr, T::BYTES, |x| x.to_radix());
+}
+
+pub fn radix_sort_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    F: FnMut(&T) -> K,
+    K: RadixKey,
+{
+    let keys = arr.iter().map(key).map(K::to_radix).collect();
+    sort_by_radix_keys(arr, keys, K::BYTES, false);
+}
+
+pub fn radix_sort_msd<T: RadixKey>(arr: &mut [T]) {
+    let mut buf = arr.to_vec();
+    let _scratch = Scratch::new::<T>(buf.len());
+    msd(arr, &mut buf, (T::BYTES as u32 - 1) * 8, &|x: &T| x.to_radix());
+}
+
+pub fn radix_sort_msd_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    F: FnMut(&T) -> K,
+    K: RadixKey,
+{
+    let keys = arr.iter().map(key).map(K::to_radix).collect();
+    sort_by_radix_keys(arr, keys, K::BYTES, true);
+}
+
+pub fn radix_sort_bytes<T: AsRef<[u8]>>(arr: &mut [T]) {
+    let order = {
+        let keys: &[T] = arr;
+        bytes_order(arr.len(), |i| keys[i].as_ref())
+    };
+    apply_order(arr, order);
+}
+
+pub fn radix_sort_bytes_by_key<T, K, F>(arr: &mut [T], key: F)
+where
+    F: FnMut(&T) -> K,
+    K: AsRef<[u8]>,
+{
+    let keys: Vec<K> = arr.iter().map(key).collect();
+    let _scratch = Scratch::new::<K>(keys.len());
+    let order = bytes_order(keys.len(), |i| keys[i].as_ref());
+    apply_order(arr, order);
+}
+
+/*
+    Sorts arr by precomputed radix keys (keys[i] belongs to arr[i]).
+    Shared with counting sort, which falls back to this for wide key ranges.
+*/
+pub(super) fn sort_by_radix_keys<T>(arr: &mut [T], keys: Vec<u64>, bytes: usize, msd_first: bool) {
+    let mut pairs: Vec<(u64, usize)> = keys.into_iter().zip(0..).collect();
+    let _scratch = Scratch::new::<(u64, usize)>(pairs.len());
+    if msd_first {
+        let mut buf = pairs.clone();
+        let _buf_scratch = Scratch::new::<(u64, usize)>(buf.len());
+        msd(&mut pairs, &mut buf, (bytes as u32 - 1) * 8, &|p: &(u64, usize)| p.0);
+    } else {
+        lsd(&mut pairs, bytes, |p| p.0);
+    }
+    apply_order(arr, pairs.into_iter().map(|p| p.1));
+}
+
+fn digit(key: u64, shift: u32) -> usize {
+    ((key >> shift) & 0xff) as usize
+}
+
+// stable LSD passes, ping-ponging between v and one scratch copy
+fn lsd<U, F>(v: &mut [U], bytes: usize, key: F)
+where
+    U: Copy,
+    F: Fn(&U) -> u64,
+{
+    let len = v.len();
+    if len < 2 {
+        return;
+    }
+
+    let mut buf = v.to_vec();
+    let _scratch = Scratch::new::<U>(len);
+    // true when the current order lives in buf
+    let mut in_buf = false;
+
+    for pass in 0..bytes {
+        let shift = pass as u32 * 8;
+        let (src, dst): (&[U], &mut [U]) = if in_buf {
+            (&buf[..], &mut v[..])
+        } else {
+            (&v[..], &mut buf[..])
+        };
+
+        let mut counts = [0usize; 256];
+        for x in src.iter() {
+            counts[digit(key(x), shift)] += 1;
+        }
+        // every key has the same byte here, the pass wouldn't move anything
+        if counts.contains(&len) {
+            continue;
+        }
+
+        let mut offsets = [0usize; 256];
+        let mut sum = 0;
+        for (offset, &count) in offsets.iter_mut().zip(counts.iter()) {
+            *offset = sum;
+            sum += count;
+        }
+        for x in src.iter() {
+            let d = digit(key(x), shift);
+            dst[offsets[d]] = *x;
+            offsets[d] += 1;
+        }
+        record_writes(dst.as_ptr(), len);
+        in_buf = !in_buf;
+    }
+
+    if in_buf {
+        v.copy_from_slice(&buf);
+        record_writes(v.as_ptr(), len);
+    }
+}
+
+// stable MSD, buf is scratch space of the same length as v
+fn msd<U, F>(v: &mut [U], buf: &mut [U], shift: u32, key: &F)
+where
+    U: Copy,
+    F: Fn(&U) -> u64,
+{
+    if v.len() <= MSD_INSERTION_THRESHOLD {
+        // remaining bytes above shift are equal, comparing the full key is fine
+        insertion_sort_range(v, &mut |a: &U, b: &U| key(a).cmp(&key(b)));
+        return;
+    }
+
+    let mut counts = [0usize; 256];
+    for x in v.iter() {
+        counts[digit(key(x), shift)] += 1;
+    }
+
+    if !counts.contains(&v.len()) {
+        let mut offsets = [0usize; 256];
+        let mut sum = 0;
+        for (offset, &count) in offsets.iter_mut().zip(counts.iter()) {
+            *offset = sum;
+            sum += count;
+        }
+        for x in v.iter() {
+            let d = digit(key(x), shift);
+            buf[offsets[d]] = *x;
+            offsets[d] += 1;
+        }
+        record_writes(buf.as_ptr(), v.len());
+        v.copy_from_slice(buf);
+        record_writes(v.as_ptr(), v.len());
+    }
+
+    if shift == 0 {
+        return;
+    }
+    let mut start = 0;
+    for &count in counts.iter() {
+        if count > 1 {
+            let end = start + count;
+            msd(&mut v[start..end], &mut buf[start..end], shift - 8, key);
+        }
+        start += count;
+    }
+}
+
+// MSD over byte strings, returns the sorted order of the indices 0..len
+fn bytes_order<'a, F>(len: usize, key: F) -> Vec<usize>
+where
+    F: Fn(usize) -> &'a [u8],
+{
+    let mut idx: Vec<usize> = (0..len).collect();
+    let mut buf = idx.clone();
+    let _scratch = Scratch::new::<usize>(2 * len);
+    msd_bytes(&mut idx, &mut buf, &key);
+    idx
+}
+
+// bucket 0 holds strings that end before depth, byte b goes to bucket b + 1
+fn byte_at(s: &[u8], depth: usize) -> usize {
+    s.get(depth).map_or(0, |&b| b as usize + 1)
+}
+
+/*
+    Buckets still to split are kept on an explicit stack as (start, end, depth), recursing would
+    take a stack frame per byte of shared prefix and overflow on keys a few KB long.
+*/
+fn msd_bytes<'a, F>(idx: &mut [usize], buf: &mut [usize], key: &F)
+where
+    F: Fn(usize) -> &'a [u8],
+{
+    let mut pending = vec![(0, idx.len(), 0)];
+    while let Some((start, end, depth)) = pending.pop() {
+        let (idx, buf) = (&mut idx[start..end], &mut buf[start..end]);
+        if idx.len() <= MSD_INSERTION_THRESHOLD {
+            // every string here shares the first depth bytes
+            insertion_sort_range(idx, &mut |&a: &usize, &b: &usize| key(a)[depth..].cmp(&key(b)[depth..]));
+            continue;
+        }
+
+        let mut counts = [0usize; 257];
+        for &i in idx.iter() {
+            counts[byte_at(key(i), depth)] += 1;
+        }
+
+        // all in one bucket (a shared byte), nothing to move
+        if !counts.contains(&idx.len()) {
+            let mut offsets = [0usize; 257];
+            let mut sum = 0;
+            for (offset, &count) in offsets.iter_mut().zip(counts.iter()) {
+                *offset = sum;
+                sum += count;
+            }
+            for &i in idx.iter() {
+                let b = byte_at(key(i), depth);
+                buf[offsets[b]] = i;
+                offsets[b] += 1;
+            }
+            record_writes(buf.as_ptr(), idx.len());
+            idx.copy_from_slice(buf);
+            record_writes(idx.as_ptr(), idx.len());
+        }
+
+        // strings that ended are all equal, only the real byte buckets go on
+        let mut bucket_start = start + counts[0];
+        for &count in counts[1..].iter() {
+            if count > 1 {
+                pending.push((bucket_start, bucket_start + count, depth + 1));
+            }
+            bucket_start += count;
+        }
+    }
+}
+
+pub struct RadixSort;
+
+impl<T: RadixKey> Sorter<T> for RadixSort {
+    fn name(&self) -> &'static str {
+        "Radix sort (LSD)"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        radix_sort(arr);
+    }
+}
+
+pub struct RadixSortMsd;
+
+impl<T: RadixKey> Sorter<T> for RadixSortMsd {
+    fn name(&self) -> &'static str {
+        "Radix sort (MSD)"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        radix_sort_msd(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_radix_sort() {
+        let mut arr = [5u32, 4, 3, 2, 1];
+        radix_sort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut arr = [5u32, 4, 3, 2, 1];
+        radix_sort_msd(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_radix_sort_300() {
+        let mut arr: Vec<u64> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        let mut msd_arr = arr.clone();
+
+        radix_sort(&mut arr);
+        radix_sort_msd(&mut msd_arr);
+        assert_eq!(arr, (0..300).collect::<Vec<_>>());
+        assert_eq!(msd_arr, arr);
+    }
+
+    #[test]
+    fn test_radix_sort_signed_and_wide() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<i64> = (0..2000).map(|_| rng.gen()).collect();
+        arr.extend([i64::MIN, i64::MAX, 0, -1, 1]);
+        let mut expected = arr.clone();
+        expected.sort();
+
+        let mut msd_arr = arr.clone();
+        radix_sort(&mut arr);
+        radix_sort_msd(&mut msd_arr);
+        assert_eq!(arr, expected);
+        assert_eq!(msd_arr, expected);
+
+        let mut small: Vec<i8> = (i8::MIN..=i8::MAX).rev().collect();
+        radix_sort(&mut small);
+        assert_eq!(small, (i8::MIN..=i8::MAX).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_radix_sort_by_key_stable() {
+        let mut rng = thread_rng();
+        // not Copy, keys collide a lot
+        let arr: Vec<(i32, String)> = (0..1000).map(|i| (rng.gen_range(-20..20), i.to_string())).collect();
+
+        for msd_first in [false, true] {
+            let mut sorted = arr.clone();
+            if msd_first {
+                radix_sort_msd_by_key(&mut sorted, |p| p.0);
+            } else {
+                radix_sort_by_key(&mut sorted, |p| p.0);
+            }
+            let mut expected = arr.clone();
+            expected.sort_by_key(|p| p.0);
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    fn test_radix_sort_bytes() {
+        let mut arr = vec!["banana", "", "apple", "app", "b", "apples", "ab", "a", "app"];
+        radix_sort_bytes(&mut arr);
+        assert_eq!(arr, ["", "a", "ab", "app", "app", "apple", "apples", "b", "banana"]);
+
+        // long enough to go through the bucket passes, with shared prefixes
+        let mut rng = thread_rng();
+        let mut words: Vec<String> = (0..3000)
+            .map(|_| {
+                let len = rng.gen_range(0..12);
+                (0..len).map(|_| rng.gen_range(b'a'..=b'd') as char).collect()
+            })
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        radix_sort_bytes(&mut words);
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_radix_sort_bytes_long_shared_prefix() {
+        // one bucket pass per byte of prefix, recursing used to overflow the stack here
+        let mut rng = thread_rng();
+        for prefix_len in [3000, 20000] {
+            let prefix = vec![b'x'; prefix_len];
+            let mut keys: Vec<Vec<u8>> = (0..200)
+                .map(|_| {
+                    let mut key = prefix.clone();
+                    key.extend((0..rng.gen_range(0..4)).map(|_| rng.gen_range(b'a'..=b'c')));
+                    key
+                })
+                .collect();
+            let mut expected = keys.clone();
+            expected.sort();
+            radix_sort_bytes(&mut keys);
+            assert_eq!(keys, expected);
+        }
+    }
+
+    #[test]
+    fn test_radix_sort_bytes_by_key() {
+        let mut arr: Vec<(u32, String)> = (0..500).map(|i| (i, format!("{:x}", i % 50))).collect();
+        radix_sort_bytes_by_key(&mut arr, |p| p.1.clone());
+        let mut expected: Vec<(u32, String)> = (0..500).map(|i| (i, format!("{:x}", i % 50))).collect();
+        expected.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(arr, expected);
+    }
+
+    // test 1000000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_radix_sort_1000000() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<u64> = (0..1000000).map(|_| rng.gen()).collect();
+        let mut expected = arr.clone();
+        expected.sort();
+        let mut msd_arr = arr.clone();
+        radix_sort(&mut arr);
+        radix_sort_msd(&mut msd_arr);
+        assert_eq!(arr, expected);
+        assert_eq!(msd_arr, expected);
+    }
+}
@@ -0,0 +1,410 @@
+/***
+ * Selection
+ * Finding the n-th smallest or the k smallest / largest elements without sorting everything
+ *
+ * - nth_element: introselect, quickselect with ninther pivots and three way partitions that
+ *   switches to median of medians past introsort's 2*floor(log2(n)) depth limit. O(n) average and worst case.
+ *   Afterwards arr[n] is the element a full sort would put there, nothing before it is bigger
+ *   and nothing after it smaller.
+ * - quickselect: plain quickselect with random pivots, O(n) expected, O(n^2) worst case
+ * - median_of_medians_select: deterministic O(n) (BFPRT), groups of five, slower in practice
+ * - partial_sort: arr[..k] becomes the k smallest elements in order, O(n log k) with a max heap
+ *   of the k smallest so far, the rest of arr ends up in no particular order
+ * - top_k: the k largest items of an iterator, largest first, O(n log k) time and O(k) memory
+ *
+ * The 99th percentile of a latency vector:
+ *     let p99 = *nth_element(&mut latencies, len * 99 / 100).unwrap();
+ *
+ * The selects return the n-th element, None if n is out of bounds (arr is left untouched).
+ */
+
+use std::cmp::Ordering;
+use std::mem;
+use super::heap_sort::{heapify, sort_heap};
+use super::insertion_sort::insertion_sort_range;
+use super::partial_order;
+use super::quick_sort::{choose_pivot, depth_limit, partition_three_way, PivotStrategy};
+use super::stats::{counted, swap, Scratch};
+
+// ranges at or below this length are finished by insertion sort
+const INSERTION_THRESHOLD: usize = 16;
+
+pub fn nth_element<T: PartialOrd>(arr: &mut [T], n: usize) -> Option<&mut T> {
+    nth_element_by(arr, n, partial_order)
+}
+
+pub fn nth_element_by<T, F>(arr: &mut [T], n: usize, compare: F) -> Option<&mut T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    if n >= arr.len() {
+        return None;
+    }
+    quick_select_range(arr, n, PivotStrategy::Ninther, depth_limit(arr.len()), &mut compare);
+    Some(&mut arr[n])
+}
+
+pub fn nth_element_by_key<T, K, F>(arr: &mut [T], n: usize, mut key: F) -> Option<&mut T>
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    nth_element_by(arr, n, |a, b| partial_order(&key(a), &key(b)))
+}
+
+pub fn quickselect<T: PartialOrd>(arr: &mut [T], n: usize) -> Option<&mut T> {
+    quickselect_by(arr, n, partial_order)
+}
+
+pub fn quickselect_by<T, F>(arr: &mut [T], n: usize, compare: F) -> Option<&mut T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    if n >= arr.len() {
+        return None;
+    }
+    quick_select_range(arr, n, PivotStrategy::Random, usize::MAX, &mut compare);
+    Some(&mut arr[n])
+}
+
+pub fn median_of_medians_select<T: PartialOrd>(arr: &mut [T], n: usize) -> Option<&mut T> {
+    median_of_medians_select_by(arr, n, partial_order)
+}
+
+pub fn median_of_medians_select_by<T, F>(arr: &mut [T], n: usize, compare: F) -> Option<&mut T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    if n >= arr.len() {
+        return None;
+    }
+    median_of_medians_range(arr, n, &mut compare);
+    Some(&mut arr[n])
+}
+
+pub fn partial_sort<T: PartialOrd>(arr: &mut [T], k: usize) {
+    partial_sort_by(arr, k, partial_order);
+}
+
+// k is clamped to arr.len(), partial_sort(arr, arr.len()) is a full heapsort
+pub fn partial_sort_by<T, F>(arr: &mut [T], k: usize, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let k = k.min(arr.len());
+    if k == 0 {
+        return;
+    }
+
+    // max heap of the k smallest so far, its root is the first to go when a smaller one shows up
+    for i in (0..k / 2).rev() {
+        heapify(arr, k, i, &mut compare);
+    }
+    for i in k..arr.len() {
+        if compare(&arr[i], &arr[0]) == Ordering::Less {
+            swap(arr, 0, i);
+            heapify(arr, k, 0, &mut compare);
+        }
+    }
+    sort_heap(&mut arr[..k], &mut compare);
+}
+
+pub fn partial_sort_by_key<T, K, F>(arr: &mut [T], k: usize, mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    partial_sort_by(arr, k, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn top_k<T, I>(iter: I, k: usize) -> Vec<T>
+where
+    T: PartialOrd,
+    I: IntoIterator<Item = T>,
+{
+    top_k_by(iter, k, partial_order)
+}
+
+// the k greatest items by compare, greatest first. Fewer than k items are all returned.
+pub fn top_k_by<T, I, F>(iter: I, k: usize, compare: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // min heap (a max heap under the reversed order) of the k greatest so far
+    let mut reversed = |a: &T, b: &T| compare(b, a);
+    let mut iter = iter.into_iter();
+    let mut heap: Vec<T> = iter.by_ref().take(k).collect();
+    let _scratch = Scratch::new::<T>(heap.len());
+    let len = heap.len();
+    for i in (0..len / 2).rev() {
+        heapify(&mut heap, len, i, &mut reversed);
+    }
+
+    for x in iter {
+        // only an item greater than the smallest kept one gets in
+        if reversed(&x, &heap[0]) == Ordering::Less {
+            heap[0] = x;
+            heapify(&mut heap, len, 0, &mut reversed);
+        }
+    }
+
+    // sorted ascending under the reversed order, so greatest first
+    sort_heap(&mut heap, &mut reversed);
+    heap
+}
+
+pub fn top_k_by_key<T, I, K, F>(iter: I, k: usize, mut key: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    top_k_by(iter, k, |a, b| partial_order(&key(a), &key(b)))
+}
+
+/*
+    Narrows arr down to the partition holding n until it is small enough for insertion sort.
+    Each round costs one depth unit, at zero the rest is handed to median of medians.
+*/
+fn quick_select_range<T, F>(mut arr: &mut [T], mut n: usize, strategy: PivotStrategy, mut depth: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if arr.len() <= INSERTION_THRESHOLD {
+            insertion_sort_range(arr, compare);
+            return;
+        }
+        if depth == 0 {
+            median_of_medians_range(arr, n, compare);
+            return;
+        }
+        depth -= 1;
+
+        let pivot = choose_pivot(arr, strategy, compare);
+        let (lt, gt) = partition_three_way(arr, pivot, compare);
+        if n < lt {
+            arr = &mut mem::take(&mut arr)[..lt];
+        } else if n >= gt {
+            arr = &mut mem::take(&mut arr)[gt..];
+            n -= gt;
+        } else {
+            // n landed among the pivot copies
+            return;
+        }
+    }
+}
+
+fn median_of_medians_range<T, F>(mut arr: &mut [T], mut n: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if arr.len() <= INSERTION_THRESHOLD {
+            insertion_sort_range(arr, compare);
+            return;
+        }
+
+        let pivot = pivot_of_medians(arr, compare);
+        let (lt, gt) = partition_three_way(arr, pivot, compare);
+        if n < lt {
+            arr = &mut mem::take(&mut arr)[..lt];
+        } else if n >= gt {
+            arr = &mut mem::take(&mut arr)[gt..];
+            n -= gt;
+        } else {
+            return;
+        }
+    }
+}
+
+/*
+    Sorts every full group of five, moves the group medians to the front and selects their median.
+    At least 3/10 of arr is <= the result and 3/10 >= it, which bounds the select to O(n).
+    Returns the index of the pivot.
+*/
+fn pivot_of_medians<T, F>(arr: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let groups = arr.len() / 5;
+    for g in 0..groups {
+        insertion_sort_range(&mut arr[5 * g..5 * g + 5], compare);
+        // slot g belongs to a group that is already done
+        swap(arr, g, 5 * g + 2);
+    }
+
+    let mid = groups / 2;
+    median_of_medians_range(&mut arr[..groups], mid, compare);
+    mid
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    type Select = fn(&mut [i32], usize) -> Option<&mut i32>;
+
+    fn selects() -> [(&'static str, Select); 3] {
+        [
+            ("nth_element", nth_element),
+            ("quickselect", quickselect),
+            ("median_of_medians_select", median_of_medians_select),
+        ]
+    }
+
+    // arr[n] is in place and the sides are split around it
+    fn assert_selected(arr: &[i32], n: usize, sorted: &[i32], name: &str) {
+        assert_eq!(arr[n], sorted[n], "{} n = {}", name, n);
+        assert!(arr[..n].iter().all(|&x| x <= arr[n]), "{} n = {}", name, n);
+        assert!(arr[n + 1..].iter().all(|&x| x >= arr[n]), "{} n = {}", name, n);
+    }
+
+    #[test]
+    fn test_select() {
+        for (name, select) in selects() {
+            let mut arr = [5, 4, 3, 2, 1];
+            assert_eq!(select(&mut arr, 1), Some(&mut 2), "{}", name);
+            assert_eq!(select(&mut arr, 5), None, "{}", name);
+            assert_eq!(select(&mut [], 0), None, "{}", name);
+        }
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_select_300() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<i32> = (0..300).map(|_| rng.gen_range(-50..50)).collect();
+        let mut sorted = arr.clone();
+        sorted.sort();
+
+        for (name, select) in selects() {
+            for n in [0, 1, 17, 150, 298, 299] {
+                arr.shuffle(&mut rng);
+                select(&mut arr, n);
+                assert_selected(&arr, n, &sorted, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_adversarial_inputs() {
+        let len = 2000;
+        let inputs: Vec<Vec<i32>> = vec![
+            (0..len).collect(),
+            (0..len).rev().collect(),
+            vec![7; len as usize],
+            (0..len).map(|i| i % 3).collect(),
+            // organ pipe
+            (0..len / 2).chain((0..len / 2).rev()).collect(),
+        ];
+
+        for input in inputs {
+            let mut sorted = input.clone();
+            sorted.sort();
+            for (name, select) in selects() {
+                for n in [0, len as usize / 2, len as usize - 1] {
+                    let mut arr = input.clone();
+                    select(&mut arr, n);
+                    assert_selected(&arr, n, &sorted, name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_nth_element_percentile() {
+        let mut rng = thread_rng();
+        let mut latencies: Vec<f64> = (0..10000).map(|_| rng.gen_range(0.5..250.0)).collect();
+        let mut sorted = latencies.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let n = latencies.len() * 99 / 100;
+        let p99 = *nth_element(&mut latencies, n).unwrap();
+        assert_eq!(p99, sorted[n]);
+    }
+
+    #[test]
+    fn test_nth_element_by_key() {
+        let mut people = vec![("cecilia", 41), ("adam", 29), ("bertil", 35), ("doris", 18)];
+        assert_eq!(nth_element_by_key(&mut people, 0, |p| p.1), Some(&mut ("doris", 18)));
+        assert_eq!(nth_element_by(&mut people, 0, |a, b| b.1.cmp(&a.1)), Some(&mut ("cecilia", 41)));
+    }
+
+    #[test]
+    fn test_partial_sort() {
+        let mut rng = thread_rng();
+        let mut arr: Vec<i32> = (0..300).collect();
+        for k in [0, 1, 10, 299, 300, 1000] {
+            arr.shuffle(&mut rng);
+            partial_sort(&mut arr, k);
+            let k = k.min(300);
+            assert_eq!(arr[..k], (0..k as i32).collect::<Vec<_>>()[..]);
+
+            // nothing got lost
+            let mut all = arr.clone();
+            all.sort();
+            assert_eq!(all, (0..300).collect::<Vec<_>>());
+        }
+
+        let mut people = vec![("cecilia", 41), ("adam", 29), ("bertil", 35), ("doris", 18)];
+        partial_sort_by_key(&mut people, 2, |p| p.1);
+        assert_eq!(people[..2], [("doris", 18), ("adam", 29)]);
+    }
+
+    #[test]
+    fn test_top_k() {
+        // works on a stream, nothing is collected up front
+        assert_eq!(top_k((0..100000).map(|x| (x * 7919) % 100000), 3), vec![99999, 99998, 99997]);
+        assert_eq!(top_k(vec![3, 1, 2], 10), vec![3, 2, 1]);
+        assert_eq!(top_k(vec![3, 1, 2], 0), Vec::<i32>::new());
+        assert_eq!(top_k(Vec::<i32>::new(), 4), Vec::<i32>::new());
+
+        // the k smallest with a reversed comparator
+        assert_eq!(top_k_by(0..50, 2, |a: &i32, b: &i32| b.cmp(a)), vec![0, 1]);
+
+        let words = ["pear", "fig", "banana", "kiwi", "apple"];
+        assert_eq!(top_k_by_key(words, 2, |w| w.len()), vec!["banana", "apple"]);
+    }
+
+    #[test]
+    fn test_top_k_random() {
+        let mut rng = thread_rng();
+        let arr: Vec<i32> = (0..1000).map(|_| rng.gen_range(0..100)).collect();
+        let mut expected = arr.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        for k in [1, 5, 64, 999, 1000] {
+            assert_eq!(top_k(arr.iter().copied(), k), expected[..k]);
+        }
+    }
+
+    // test 1000000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_select_1000000() {
+        let mut arr: Vec<i32> = (0..1000000).collect();
+        arr.shuffle(&mut thread_rng());
+        for (name, select) in selects() {
+            let mut arr = arr.clone();
+            assert_eq!(select(&mut arr, 990000), Some(&mut 990000), "{}", name);
+        }
+        partial_sort(&mut arr, 1000);
+        assert_eq!(arr[..1000], (0..1000).collect::<Vec<_>>()[..]);
+    }
+}
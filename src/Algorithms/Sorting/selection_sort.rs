@@ -1,25 +1,52 @@
-use std::ops::Index;
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
 
-pub fn selectionsort<T, O>(container: &mut T)
+pub fn selectionsort<T: PartialOrd>(arr: &mut [T]) {
+    selectionsort_by(arr, partial_order);
+}
+
+pub fn selectionsort_by<T, F>(arr: &mut [T], compare: F)
 where
-    T: Index<usize, Output = O> + AsMut<[O]> + AsRef<[O]> + PartialOrd + Sized,
-    O: PartialOrd,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    let len = container.as_ref().len();
+    let mut compare = counted(compare);
+    let len = arr.len();
 
     for i in 0..len {
         let mut min = i;
         for j in i + 1..len {
-            if container.as_ref()[j] < container.as_ref()[min] {
+            if compare(&arr[j], &arr[min]) == Ordering::Less {
                 min = j;
             }
         }
         if min != i {
-            container.as_mut().swap(i, min);
+            swap(arr, i, min);
         }
     }
 }
 
+pub fn selectionsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    selectionsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub struct SelectionSort;
+
+impl<T: PartialOrd> Sorter<T> for SelectionSort {
+    fn name(&self) -> &'static str {
+        "Selection sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        selectionsort(arr);
+    }
+}
+
 
 
 // **********  TESTS ********** //
@@ -69,5 +96,11 @@ mod tests {
         }
     }
 
-
+    #[test]
+    fn test_selection_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        selectionsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,214 @@
+/***
+ * Shell Sort
+ * In place, unstable, complexity depends on the gap sequence, O(n^2) with Shell's original gaps
+ *
+ * Insertion sort over elements gap apart, for a shrinking sequence of gaps ending in 1.
+ * Large gaps move far out of place elements long distances early, so the final
+ * plain insertion sort (gap 1) only has little work left.
+ *
+ * Gap sequences:
+ * - Shell:     n/2, n/4, ..., 1                      O(n^2) worst case
+ * - Knuth:     1, 4, 13, 40, ... (3^k - 1) / 2        O(n^(3/2))
+ * - Sedgewick: 1, 8, 23, 77, ... 4^k + 3*2^(k-1) + 1  O(n^(4/3))
+ * - Ciura:     1, 4, 10, 23, 57, 132, 301, 701, 1750, then * 2.25, best known in practice
+ */
+
+use std::cmp::Ordering;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, swap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    Shell,
+    Knuth,
+    Sedgewick,
+    Ciura,
+}
+
+// empirically found by Marcin Ciura, extended geometrically past the last one
+const CIURA_GAPS: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+
+impl GapSequence {
+    // gaps to use for a slice of len elements, largest first, always ending in 1
+    pub fn gaps(&self, len: usize) -> Vec<usize> {
+        let mut gaps = Vec::new();
+        match self {
+            GapSequence::Shell => {
+                let mut gap = len / 2;
+                while gap > 1 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.push(1);
+                gaps.reverse();
+            }
+            GapSequence::Knuth => {
+                // Knuth suggests stopping below n / 3
+                let mut gap = 1;
+                while gap == 1 || gap < len.div_ceil(3) {
+                    gaps.push(gap);
+                    gap = 3 * gap + 1;
+                }
+            }
+            GapSequence::Sedgewick => {
+                gaps.push(1);
+                let mut k = 1;
+                loop {
+                    let gap = 4usize.pow(k) + 3 * 2usize.pow(k - 1) + 1;
+                    if gap >= len {
+                        break;
+                    }
+                    gaps.push(gap);
+                    k += 1;
+                }
+            }
+            GapSequence::Ciura => {
+                gaps.extend(CIURA_GAPS.iter().take_while(|&&gap| gap == 1 || gap < len));
+                if gaps.len() == CIURA_GAPS.len() {
+                    let mut gap = CIURA_GAPS[CIURA_GAPS.len() - 1];
+                    loop {
+                        gap = (gap as f64 * 2.25) as usize;
+                        if gap >= len {
+                            break;
+                        }
+                        gaps.push(gap);
+                    }
+                }
+            }
+        }
+        // built smallest first above
+        gaps.reverse();
+        gaps
+    }
+}
+
+pub fn shellsort<T: PartialOrd>(arr: &mut [T]) {
+    shellsort_with(arr, GapSequence::Ciura);
+}
+
+pub fn shellsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shellsort_with_by(arr, GapSequence::Ciura, compare);
+}
+
+pub fn shellsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    shellsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+pub fn shellsort_with<T: PartialOrd>(arr: &mut [T], gaps: GapSequence) {
+    shellsort_with_by(arr, gaps, partial_order);
+}
+
+pub fn shellsort_with_by<T, F>(arr: &mut [T], gaps: GapSequence, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+
+    for gap in gaps.gaps(len) {
+        // insertion sort on every gap-th element
+        for i in gap..len {
+            let mut j = i;
+            while j >= gap && compare(&arr[j - gap], &arr[j]) == Ordering::Greater {
+                swap(arr, j - gap, j);
+                j -= gap;
+            }
+        }
+    }
+}
+
+pub struct ShellSort;
+
+impl<T: PartialOrd> Sorter<T> for ShellSort {
+    fn name(&self) -> &'static str {
+        "Shell sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        shellsort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    const SEQUENCES: [GapSequence; 4] = [
+        GapSequence::Shell,
+        GapSequence::Knuth,
+        GapSequence::Sedgewick,
+        GapSequence::Ciura,
+    ];
+
+    #[test]
+    fn test_shell_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        shellsort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_shell_sort_300() {
+        for gaps in SEQUENCES {
+            let mut arr: Vec<_> = (0..300).collect();
+            arr.shuffle(&mut thread_rng());
+            shellsort_with(&mut arr, gaps);
+            assert_eq!(arr, (0..300).collect::<Vec<_>>(), "{:?}", gaps);
+        }
+    }
+
+    #[test]
+    fn test_gap_sequences() {
+        assert_eq!(GapSequence::Shell.gaps(100), vec![50, 25, 12, 6, 3, 1]);
+        assert_eq!(GapSequence::Knuth.gaps(100), vec![13, 4, 1]);
+        assert_eq!(GapSequence::Sedgewick.gaps(100), vec![77, 23, 8, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(100), vec![57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(5000), vec![3937, 1750, 701, 301, 132, 57, 23, 10, 4, 1]);
+
+        for gaps in SEQUENCES {
+            for len in 0..200 {
+                let seq = gaps.gaps(len);
+                assert_eq!(seq.last(), Some(&1), "{:?} {}", gaps, len);
+                assert!(seq.windows(2).all(|w| w[0] > w[1]), "{:?} {}", gaps, len);
+            }
+        }
+    }
+
+    // test 10000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_shell_sort_10000() {
+        for gaps in SEQUENCES {
+            let mut arr: Vec<_> = (0..10000).collect();
+            arr.shuffle(&mut thread_rng());
+            shellsort_with(&mut arr, gaps);
+            assert_eq!(arr, (0..10000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_shell_sort_by_descending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        shellsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..300).rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,95 @@
+/***
+ * Sorter
+ * Common entry point implemented by every sorting algorithm in this module.
+ * Each algorithm exposes a unit struct (BubbleSort, QuickSort, ...) so callers and
+ * the benches can hold a list of `Box<dyn Sorter<T>>` and pick one at runtime.
+ */
+
+use super::bubble_sort::BubbleSort;
+use super::cocktail_sort::CocktailSort;
+use super::comb_sort::CombSort;
+use super::counting_sort::CountingSort;
+use super::gnome_sort::GnomeSort;
+use super::heap_sort::HeapSort;
+use super::insertion_sort::InsertionSort;
+use super::merge_sort::{MergeSort, MergeSortBottomUp, MergeSortNatural};
+use super::quick_sort::{IntroSort, QuickSort};
+use super::radix_sort::{RadixKey, RadixSort, RadixSortMsd};
+use super::selection_sort::SelectionSort;
+use super::shell_sort::ShellSort;
+use super::stats::{measure, SortStats};
+use super::tim_sort::TimSort;
+
+pub trait Sorter<T> {
+    // human readable name, also used as the bench id
+    fn name(&self) -> &'static str;
+
+    // sorts arr in ascending order
+    fn sort(&self, arr: &mut [T]);
+
+    // sort in instrumented mode, see stats::measure
+    fn sort_with_stats(&self, arr: &mut [T]) -> SortStats {
+        measure(arr, |arr| self.sort(arr))
+    }
+}
+
+// every comparison sort in the module, in the order the benches report them
+pub fn all_sorters<T: PartialOrd>() -> Vec<Box<dyn Sorter<T>>> {
+    vec![
+        Box::new(BubbleSort),
+        Box::new(InsertionSort),
+        Box::new(SelectionSort),
+        Box::new(CocktailSort),
+        Box::new(GnomeSort),
+        Box::new(CombSort),
+        Box::new(ShellSort),
+        Box::new(MergeSort),
+        Box::new(MergeSortBottomUp),
+        Box::new(MergeSortNatural),
+        Box::new(TimSort),
+        Box::new(QuickSort),
+        Box::new(HeapSort),
+        Box::new(IntroSort),
+    ]
+}
+
+// all_sorters plus the non-comparison sorts that only work on integers
+pub fn all_integer_sorters<T: PartialOrd + RadixKey>() -> Vec<Box<dyn Sorter<T>>> {
+    let mut sorters = all_sorters();
+    sorters.push(Box::new(CountingSort));
+    sorters.push(Box::new(RadixSort));
+    sorters.push(Box::new(RadixSortMsd));
+    sorters
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn every_sorter_sorts() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+
+        for sorter in all_integer_sorters::<i32>() {
+            let mut arr_clone = arr.clone();
+            sorter.sort(&mut arr_clone);
+            assert_eq!(arr_clone, (0..300).collect::<Vec<_>>(), "{} failed", sorter.name());
+        }
+    }
+
+    #[test]
+    fn sorter_names_are_unique() {
+        let sorters = all_integer_sorters::<i32>();
+        let mut names: Vec<_> = sorters.iter().map(|s| s.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), sorters.len());
+    }
+}
@@ -0,0 +1,371 @@
+/***
+ * Sorting Network
+ * Fixed sequences of compare exchanges for 2..=16 elements, unstable, no branches on the data
+ *
+ * Every network is a list of (a, b) pairs with a < b: put the smaller of arr[a], arr[b] at a.
+ * Which pairs get compared never depends on the data, so there is nothing to mispredict and
+ * independent exchanges (one line of a table below) can run in parallel in the CPU.
+ * Sizes are the smallest known: proven optimal up to 12 elements, best known for 13..=16
+ * (13 from Dobbelaere's list, 14 and 15 pruned from Green's 60 comparator network for 16).
+ *
+ * - network_sort: slices of up to 16 elements, longer ones are handed to introsort
+ * - small_sort / small_sort_stable: the base cases of quicksort / introsort and mergesort.
+ *   The stable one runs the network over positions with ties broken by position, then moves
+ *   every element once.
+ *
+ * Upsides:
+ * - Beats insertion sort on random small inputs, there are no unpredictable branches
+ *
+ * Downsides:
+ * - Always does every comparison, sorted input doesn't finish early
+ * - Only for small fixed sizes
+ */
+
+use std::cmp::Ordering;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use super::partial_order;
+use super::quick_sort::introsort_by;
+use super::stats::{counted, record_exchange, swap};
+
+// longest slice with a network
+pub const MAX_NETWORK_LEN: usize = 16;
+
+// the comparator pairs of the network for len elements, None past MAX_NETWORK_LEN
+pub fn sorting_network(len: usize) -> Option<&'static [(u8, u8)]> {
+    NETWORKS.get(len).copied()
+}
+
+pub fn network_sort<T: PartialOrd>(arr: &mut [T]) {
+    network_sort_by(arr, partial_order);
+}
+
+pub fn network_sort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() > MAX_NETWORK_LEN {
+        introsort_by(arr, compare);
+        return;
+    }
+    small_sort(arr, &mut counted(compare));
+}
+
+pub fn network_sort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    network_sort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+// precondition: arr.len() <= MAX_NETWORK_LEN
+pub(super) fn small_sort<T, F>(arr: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for &(a, b) in NETWORKS[arr.len()] {
+        compare_exchange(arr, a as usize, b as usize, compare);
+    }
+}
+
+// precondition: arr.len() <= MAX_NETWORK_LEN
+pub(super) fn small_sort_stable<T, F>(arr: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    // order[k] = position of the element that belongs at k, a total order with the ties broken
+    let mut order = [0u8; MAX_NETWORK_LEN];
+    for (k, slot) in order.iter_mut().enumerate() {
+        *slot = k as u8;
+    }
+    for &(a, b) in NETWORKS[len] {
+        let (x, y) = (order[a as usize], order[b as usize]);
+        let greater = compare(&arr[x as usize], &arr[y as usize]).then(x.cmp(&y)) == Ordering::Greater;
+        order[a as usize] = if greater { y } else { x };
+        order[b as usize] = if greater { x } else { y };
+    }
+
+    // follow the permutation cycles, like apply_order but without allocating
+    let mut dest = [0u8; MAX_NETWORK_LEN];
+    for (k, &from) in order[..len].iter().enumerate() {
+        dest[from as usize] = k as u8;
+    }
+    for i in 0..len {
+        while dest[i] as usize != i {
+            let d = dest[i] as usize;
+            swap(arr, i, d);
+            dest.swap(i, d);
+        }
+    }
+}
+
+/*
+    Puts the smaller of arr[a] and arr[b] at a, equal elements stay put.
+    Both slots are rewritten whatever the outcome, the comparison only picks the sources,
+    which compiles to conditional moves instead of a branch.
+*/
+fn compare_exchange<T, F>(arr: &mut [T], a: usize, b: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // the tables only hold valid pairs for their length
+    debug_assert!(a < b && b < arr.len());
+    // a panicking comparator leaves before anything moved
+    let swapped = compare(&arr[b], &arr[a]) == Ordering::Less;
+    unsafe {
+        let v = arr.as_mut_ptr();
+        let (pa, pb) = (v.add(a), v.add(b));
+        let small = ManuallyDrop::new(ptr::read(if swapped { pb } else { pa }));
+        ptr::copy(if swapped { pa } else { pb }, pb, 1);
+        ptr::copy_nonoverlapping(&*small, pa, 1);
+    }
+    record_exchange(arr, a, b, swapped);
+}
+
+// one line per layer, the exchanges on a line touch disjoint slots
+const NETWORK_2: [(u8, u8); 1] = [
+    (0, 1),
+];
+const NETWORK_3: [(u8, u8); 3] = [
+    (0, 2),
+    (0, 1),
+    (1, 2),
+];
+const NETWORK_4: [(u8, u8); 5] = [
+    (0, 1), (2, 3),
+    (0, 2), (1, 3),
+    (1, 2),
+];
+const NETWORK_5: [(u8, u8); 9] = [
+    (0, 3), (1, 4),
+    (0, 2), (1, 3),
+    (0, 1), (2, 4),
+    (1, 2), (3, 4),
+    (2, 3),
+];
+const NETWORK_6: [(u8, u8); 12] = [
+    (0, 5), (1, 3), (2, 4),
+    (1, 2), (3, 4),
+    (0, 3), (2, 5),
+    (0, 1), (2, 3), (4, 5),
+    (1, 2), (3, 4),
+];
+const NETWORK_7: [(u8, u8); 16] = [
+    (0, 6), (2, 3), (4, 5),
+    (0, 2), (1, 4), (3, 6),
+    (0, 1), (2, 5), (3, 4),
+    (1, 2), (4, 6),
+    (2, 3), (4, 5),
+    (1, 2), (3, 4), (5, 6),
+];
+const NETWORK_8: [(u8, u8); 19] = [
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (2, 4), (3, 5),
+    (1, 4), (3, 6),
+    (1, 2), (3, 4), (5, 6),
+];
+const NETWORK_9: [(u8, u8); 25] = [
+    (0, 3), (1, 7), (2, 5), (4, 8),
+    (0, 7), (2, 4), (3, 8), (5, 6),
+    (0, 2), (1, 3), (4, 5), (7, 8),
+    (1, 4), (3, 6), (5, 7),
+    (0, 1), (2, 4), (3, 5), (6, 8),
+    (2, 3), (4, 5), (6, 7),
+    (1, 2), (3, 4), (5, 6),
+];
+const NETWORK_10: [(u8, u8); 29] = [
+    (0, 8), (1, 9), (2, 7), (3, 5), (4, 6),
+    (0, 2), (1, 4), (5, 8), (7, 9),
+    (0, 3), (2, 4), (5, 7), (6, 9),
+    (0, 1), (3, 6), (8, 9),
+    (1, 5), (2, 3), (4, 8), (6, 7),
+    (1, 2), (3, 5), (4, 6), (7, 8),
+    (2, 3), (4, 5), (6, 7),
+    (3, 4), (5, 6),
+];
+const NETWORK_11: [(u8, u8); 35] = [
+    (0, 9), (1, 6), (2, 4), (3, 7), (5, 8),
+    (0, 1), (3, 5), (4, 10), (6, 9), (7, 8),
+    (1, 3), (2, 5), (4, 7), (8, 10),
+    (0, 4), (1, 2), (3, 7), (5, 9), (6, 8),
+    (0, 1), (2, 6), (4, 5), (7, 8), (9, 10),
+    (2, 4), (3, 6), (5, 7), (8, 9),
+    (1, 2), (3, 4), (5, 6), (7, 8),
+    (2, 3), (4, 5), (6, 7),
+];
+const NETWORK_12: [(u8, u8); 39] = [
+    (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9),
+    (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11),
+    (0, 2), (1, 6), (5, 10), (9, 11),
+    (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+    (1, 4), (3, 5), (6, 8), (7, 10),
+    (1, 3), (2, 5), (6, 9), (8, 10),
+    (2, 3), (4, 5), (6, 7), (8, 9),
+    (4, 6), (5, 7),
+    (3, 4), (5, 6), (7, 8),
+];
+const NETWORK_13: [(u8, u8); 45] = [
+    (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8),
+    (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+    (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12),
+    (4, 6), (5, 9), (8, 11), (10, 12),
+    (0, 5), (3, 8), (4, 7), (6, 11), (9, 10),
+    (0, 1), (2, 5), (6, 9), (7, 8), (10, 11),
+    (1, 3), (2, 4), (5, 6), (9, 10),
+    (1, 2), (3, 4), (5, 7), (6, 8),
+    (2, 3), (4, 5), (6, 7), (8, 9),
+    (3, 4), (5, 6),
+];
+const NETWORK_14: [(u8, u8); 51] = [
+    (0, 13), (1, 12), (4, 8), (5, 6), (7, 11), (9, 10),
+    (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (11, 12),
+    (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13),
+    (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11),
+    (1, 4), (2, 6), (5, 8), (7, 10), (9, 13),
+    (2, 4), (3, 6), (9, 12), (11, 13),
+    (3, 5), (6, 8), (7, 9), (10, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+    (6, 7), (8, 9),
+];
+const NETWORK_15: [(u8, u8); 56] = [
+    (0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+    (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (11, 12),
+    (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13),
+    (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+    (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+    (2, 4), (3, 6), (9, 12), (11, 13),
+    (3, 5), (6, 8), (7, 9), (10, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+    (6, 7), (8, 9),
+];
+const NETWORK_16: [(u8, u8); 60] = [
+    (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+    (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12),
+    (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15),
+    (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+    (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+    (2, 4), (3, 6), (9, 12), (11, 13),
+    (3, 5), (6, 8), (7, 9), (10, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+    (6, 7), (8, 9),
+];
+
+static NETWORKS: [&[(u8, u8)]; MAX_NETWORK_LEN + 1] = [
+    &[], &[], &NETWORK_2, &NETWORK_3, &NETWORK_4, &NETWORK_5, &NETWORK_6, &NETWORK_7, &NETWORK_8,
+    &NETWORK_9, &NETWORK_10, &NETWORK_11, &NETWORK_12, &NETWORK_13, &NETWORK_14, &NETWORK_15, &NETWORK_16,
+];
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_network_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        network_sort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        network_sort(&mut empty);
+        let mut one = [1];
+        network_sort(&mut one);
+        assert_eq!(one, [1]);
+    }
+
+    // 0-1 principle: a network that sorts every sequence of 0s and 1s sorts everything
+    #[test]
+    fn test_networks_sort_all_binary_inputs() {
+        for len in 2..=MAX_NETWORK_LEN {
+            for bits in 0u32..1 << len {
+                let mut arr: Vec<u8> = (0..len).map(|i| (bits >> i) as u8 & 1).collect();
+                network_sort(&mut arr);
+                assert!(arr.windows(2).all(|w| w[0] <= w[1]), "len {} input {:b}", len, bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_sizes() {
+        let best_known = [0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60];
+        for (len, &size) in best_known.iter().enumerate() {
+            let network = sorting_network(len).unwrap();
+            assert_eq!(network.len(), size, "len {}", len);
+            assert!(network.iter().all(|&(a, b)| a < b && (b as usize) < len));
+        }
+        assert_eq!(sorting_network(17), None);
+    }
+
+    #[test]
+    fn test_network_sort_random() {
+        let mut rng = thread_rng();
+        for len in 0..=40 {
+            let mut arr: Vec<i32> = (0..len).map(|_| rng.gen_range(-5..5)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            network_sort(&mut arr);
+            assert_eq!(arr, expected);
+        }
+
+        // not Copy, every element is moved around without being duplicated or lost
+        let mut words: Vec<String> = (0..16).map(|i| format!("{:02}", i)).collect();
+        words.shuffle(&mut rng);
+        network_sort_by_key(&mut words, |w| w.clone());
+        assert_eq!(words, (0..16).map(|i| format!("{:02}", i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_small_sort_stable() {
+        let mut rng = thread_rng();
+        for len in 0..=MAX_NETWORK_LEN {
+            let mut arr: Vec<(u8, usize)> = (0..len).map(|i| (rng.gen_range(0..3), i)).collect();
+            let mut expected = arr.clone();
+            expected.sort_by_key(|p| p.0);
+            small_sort_stable(&mut arr, &mut |a: &(u8, usize), b: &(u8, usize)| a.0.cmp(&b.0));
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_network_sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut arr: Vec<String> = (0..16).map(|i| i.to_string()).collect();
+        arr.shuffle(&mut thread_rng());
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            network_sort_by(&mut arr, |a, b| {
+                calls += 1;
+                if calls == 30 {
+                    panic!("comparator gave up");
+                }
+                a.cmp(b)
+            });
+        }));
+        assert!(result.is_err());
+        arr.sort();
+        let mut expected: Vec<String> = (0..16).map(|i| i.to_string()).collect();
+        expected.sort();
+        assert_eq!(arr, expected);
+    }
+
+    #[test]
+    fn test_network_sort_by_descending() {
+        let mut arr: Vec<i32> = (0..12).collect();
+        arr.shuffle(&mut thread_rng());
+        network_sort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..12).rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,401 @@
+/***
+ * Sort Stats
+ * Instrumented mode for every sort in this module: comparisons, swaps, moves and peak scratch memory
+ *
+ * measure(arr, |arr| quicksort(arr)) runs the sort with a recorder installed on the current
+ * thread and returns what it counted. Outside of measure every hook returns after reading a
+ * single thread local flag, no lookup of the recorder, no reference counting.
+ *
+ * - comparisons: every *_by sort wraps the comparator it was given in counted(), so plain,
+ *   *_by and *_by_key sorts are all covered, custom comparators included
+ * - swaps: element swaps inside the slice
+ * - moves: single element writes that aren't swaps (merges, buffer copies, rotations)
+ * - peak_memory: largest amount of scratch space (bytes) the sort held at once,
+ *   buffers are reported by the algorithms themselves through Scratch, not by the allocator,
+ *   so the numbers are exact and unaffected by other threads or tests running at the same time
+ *
+ * Parallel sorts hand the recorder to their workers, their counts end up in the same stats.
+ * The same hooks feed the step by step events of trace.rs.
+ */
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use super::trace::{SortEvent, Tracer};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortStats {
+    pub comparisons: u64,
+    pub swaps: u64,
+    pub moves: u64,
+    pub peak_memory: usize, // bytes
+}
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    comparisons: AtomicU64,
+    swaps: AtomicU64,
+    moves: AtomicU64,
+    memory: AtomicUsize,
+    peak_memory: AtomicUsize,
+    tracer: Option<Tracer>, // set when the sort runs under trace
+}
+
+impl Counters {
+    pub(crate) fn new(tracer: Option<Tracer>) -> Self {
+        Counters { tracer, ..Counters::default() }
+    }
+
+    // runs sort with self recording on the current thread
+    pub(crate) fn record<T, F>(self, arr: &mut [T], sort: F) -> SortStats
+    where
+        F: FnOnce(&mut [T]),
+    {
+        let counters = Arc::new(self);
+        Recorder(Some(counters.clone())).run(|| sort(arr));
+
+        SortStats {
+            comparisons: counters.comparisons.load(AtomicOrdering::Relaxed),
+            swaps: counters.swaps.load(AtomicOrdering::Relaxed),
+            moves: counters.moves.load(AtomicOrdering::Relaxed),
+            peak_memory: counters.peak_memory.load(AtomicOrdering::Relaxed),
+        }
+    }
+}
+
+thread_local! {
+    // true while RECORDER is Some, all a hook reads when nothing is being measured
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+    static RECORDER: Cell<Option<Arc<Counters>>> = const { Cell::new(None) };
+}
+
+// swaps the recorder of the current thread for recorder, returns the previous one
+fn install(recorder: Option<Arc<Counters>>) -> Option<Arc<Counters>> {
+    RECORDING.set(recorder.is_some());
+    RECORDER.replace(recorder)
+}
+
+// installs the recorder it holds when dropped, also when unwinding
+struct Restore(Option<Arc<Counters>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        install(self.0.take());
+    }
+}
+
+// runs sort on arr and returns what it did, sort is typically a closure calling one of the sorts
+pub fn measure<T, F>(arr: &mut [T], sort: F) -> SortStats
+where
+    F: FnOnce(&mut [T]),
+{
+    Counters::new(None).record(arr, sort)
+}
+
+// the comparator every *_by sort runs with, each call is counted as a comparison
+pub(crate) fn counted<T, F>(mut compare: F) -> impl FnMut(&T, &T) -> Ordering
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    move |a, b| {
+        record_comparison(a, b);
+        compare(a, b)
+    }
+}
+
+/*
+    The recorder active on a thread. Parallel sorts grab the current one before spawning
+    and run every worker inside it.
+*/
+#[derive(Clone)]
+pub(crate) struct Recorder(Option<Arc<Counters>>);
+
+impl Recorder {
+    pub(crate) fn current() -> Self {
+        let current = RECORDER.take();
+        RECORDER.set(current.clone());
+        Recorder(current)
+    }
+
+    // installs self for the duration of f, the previous recorder is restored even if f panics
+    pub(crate) fn run<R>(self, f: impl FnOnce() -> R) -> R {
+        let _restore = Restore(install(self.0));
+        f()
+    }
+}
+
+fn with_counters(f: impl FnOnce(&Counters)) {
+    if !RECORDING.get() {
+        return;
+    }
+    /*
+        Moved out for the duration of f rather than cloned, no reference counting per event.
+        It also means nothing f runs is recorded: a trace observer that sorts something itself
+        doesn't feed back into the trace.
+    */
+    let restore = Restore(install(None));
+    if let Some(counters) = restore.0.as_ref() {
+        f(counters);
+    }
+}
+
+fn with_tracer(f: impl FnOnce(&Tracer)) {
+    with_counters(|c| {
+        if let Some(tracer) = c.tracer.as_ref() {
+            f(tracer);
+        }
+    });
+}
+
+/*
+    Hooks called by the algorithms. Positions are passed as pointers or sub-slices,
+    the tracer maps them back to indices in the slice being sorted.
+*/
+
+pub(crate) fn record_comparison<T>(a: &T, b: &T) {
+    with_counters(|c| {
+        c.comparisons.fetch_add(1, AtomicOrdering::Relaxed);
+        if let Some(tracer) = c.tracer.as_ref() {
+            // comparisons against scratch buffers or computed keys have no index
+            if let (Some(i), Some(j)) = (tracer.index_of(a), tracer.index_of(b)) {
+                tracer.emit(SortEvent::Compare(i, j));
+            }
+        }
+    });
+}
+
+// count elements written starting at dest, which may be in the slice or in a scratch buffer
+pub(crate) fn record_writes<T>(dest: *const T, count: usize) {
+    with_counters(|c| {
+        c.moves.fetch_add(count as u64, AtomicOrdering::Relaxed);
+        if let Some(tracer) = c.tracer.as_ref() {
+            if let Some(start) = tracer.index_of(dest) {
+                for i in start..start + count {
+                    tracer.emit(SortEvent::Write(i));
+                }
+            }
+        }
+    });
+}
+
+// arr.swap that is counted
+pub(crate) fn swap<T>(arr: &mut [T], a: usize, b: usize) {
+    arr.swap(a, b);
+    record_exchange(arr, a, b, true);
+}
+
+// a compare exchange of arr[a] and arr[b] that already happened, counted as a swap if it swapped
+pub(crate) fn record_exchange<T>(arr: &[T], a: usize, b: usize, swapped: bool) {
+    with_counters(|c| {
+        if !swapped {
+            return;
+        }
+        c.swaps.fetch_add(1, AtomicOrdering::Relaxed);
+        if let Some(tracer) = c.tracer.as_ref() {
+            if let Some(base) = tracer.index_of(arr.as_ptr()) {
+                tracer.emit(SortEvent::Swap(base + a, base + b));
+            }
+        }
+    });
+}
+
+// arr.reverse that is counted, as the swaps it does
+pub(crate) fn reverse<T>(arr: &mut [T]) {
+    arr.reverse();
+    let len = arr.len();
+    with_counters(|c| {
+        c.swaps.fetch_add((len / 2) as u64, AtomicOrdering::Relaxed);
+        if let Some(tracer) = c.tracer.as_ref() {
+            if let Some(base) = tracer.index_of(arr.as_ptr()) {
+                for k in 0..len / 2 {
+                    tracer.emit(SortEvent::Swap(base + k, base + len - 1 - k));
+                }
+            }
+        }
+    });
+}
+
+// arr[lt..gt] == pivot, nothing in arr[..lt] is bigger and nothing in arr[gt..] smaller
+pub(crate) fn record_partition<T>(arr: &[T], lt: usize, gt: usize) {
+    with_tracer(|tracer| {
+        if let Some(start) = tracer.index_of(arr.as_ptr()) {
+            tracer.emit(SortEvent::Partition {
+                start,
+                end: start + arr.len(),
+                lt: start + lt,
+                gt: start + gt,
+            });
+        }
+    });
+}
+
+// arr[..mid] and arr[mid..] are about to be merged
+pub(crate) fn record_merge<T>(arr: &[T], mid: usize) {
+    with_tracer(|tracer| {
+        if let Some(start) = tracer.index_of(arr.as_ptr()) {
+            tracer.emit(SortEvent::Merge {
+                start,
+                mid: start + mid,
+                end: start + arr.len(),
+            });
+        }
+    });
+}
+
+/*
+    Reports a scratch buffer of len elements of T for as long as it is alive,
+    created next to the buffer it describes.
+*/
+pub(crate) struct Scratch {
+    bytes: usize,
+}
+
+impl Scratch {
+    pub(crate) fn new<T>(len: usize) -> Self {
+        let bytes = len * mem::size_of::<T>();
+        with_counters(|c| {
+            let now = c.memory.fetch_add(bytes, AtomicOrdering::Relaxed) + bytes;
+            c.peak_memory.fetch_max(now, AtomicOrdering::Relaxed);
+        });
+        Scratch { bytes }
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        with_counters(|c| {
+            c.memory.fetch_sub(self.bytes, AtomicOrdering::Relaxed);
+        });
+    }
+}
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bubble_sort::bubblesort;
+    use super::super::bucket_sort::bucket_sort;
+    use super::super::counting_sort::counting_sort;
+    use super::super::insertion_sort::{insertionsort, insertionsort_by};
+    use super::super::quick_sort::quicksort_by;
+    use super::super::merge_sort::mergesort;
+    use super::super::parallel_sort::{par_mergesort_with, ParallelConfig};
+    use super::super::quick_sort::quicksort_by_key;
+    use super::super::radix_sort::{radix_sort, radix_sort_bytes, radix_sort_msd};
+    use super::super::sorter::all_integer_sorters;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_stats_bubble_sort_reversed() {
+        // reversed input: every pair is compared and swapped once
+        let n = 100u64;
+        let mut arr: Vec<u64> = (0..n).rev().collect();
+        let stats = measure(&mut arr, bubblesort);
+        assert_eq!(stats.comparisons, n * (n - 1) / 2);
+        assert_eq!(stats.swaps, n * (n - 1) / 2);
+        assert_eq!(stats.moves, 0);
+        assert_eq!(stats.peak_memory, 0);
+    }
+
+    #[test]
+    fn test_stats_insertion_sort_sorted() {
+        let mut arr: Vec<u32> = (0..1000).collect();
+        let stats = measure(&mut arr, insertionsort);
+        assert_eq!(stats, SortStats { comparisons: 999, ..SortStats::default() });
+    }
+
+    #[test]
+    fn test_stats_merge_sort_memory() {
+        let mut arr: Vec<u64> = (0..1000).collect();
+        arr.shuffle(&mut thread_rng());
+        let stats = measure(&mut arr, mergesort);
+        // one buffer of n / 2 elements for the whole sort
+        assert_eq!(stats.peak_memory, 500 * mem::size_of::<u64>());
+        assert!(stats.moves > 0);
+        // n log2 n, give or take the insertion sorted leaves
+        assert!(stats.comparisons >= 1000 * 10 / 2 && stats.comparisons <= 1000 * 10 * 2);
+    }
+
+    #[test]
+    fn test_stats_custom_comparators() {
+        let mut arr: Vec<i32> = (0..50).collect();
+
+        // reversing: every comparison says Greater and is followed by a swap
+        let stats = measure(&mut arr, |arr| insertionsort_by(arr, |a, b| b.cmp(a)));
+        assert_eq!(stats.comparisons, 50 * 49 / 2);
+        assert_eq!(stats.swaps, 50 * 49 / 2);
+
+        // counted once, not again in the _by sorts they delegate to
+        let mut calls = 0;
+        let stats = measure(&mut arr, |arr| {
+            quicksort_by(arr, |a, b| {
+                calls += 1;
+                a.cmp(b)
+            })
+        });
+        assert_eq!(stats.comparisons, calls);
+
+        let stats = measure(&mut arr, |arr| quicksort_by_key(arr, |x| -x));
+        assert!(stats.comparisons > 0);
+    }
+
+    #[test]
+    fn test_stats_parallel_workers_are_counted() {
+        let mut arr: Vec<u32> = (0..4000).collect();
+        arr.shuffle(&mut thread_rng());
+        let stats = measure(&mut arr, |arr| par_mergesort_with(arr, ParallelConfig::new(4, 100)));
+        // every element takes part in at least one comparison
+        assert!(stats.comparisons >= 2000);
+        assert!(stats.moves > 0);
+        assert!(stats.peak_memory > 0);
+    }
+
+    #[test]
+    fn test_stats_only_inside_measure() {
+        let mut arr: Vec<u32> = (0..100).rev().collect();
+        bubblesort(&mut arr);
+        let stats = measure(&mut arr, |_| {});
+        assert_eq!(stats, SortStats::default());
+
+        // the recorder is gone after a panicking sort too
+        let _ = std::panic::catch_unwind(|| {
+            measure(&mut [2, 1], |_| panic!("sort gave up"));
+        });
+        assert!(!RECORDING.get());
+        assert!(RECORDER.take().is_none());
+    }
+
+    #[test]
+    fn test_stats_non_comparison_sorts_compare_nothing() {
+        let mut rng = thread_rng();
+        let arr: Vec<u64> = (0..5000).map(|_| rng.gen_range(0..100_000)).collect();
+        let sorts: [fn(&mut [u64]); 3] = [radix_sort, radix_sort_msd, counting_sort];
+        for sort in sorts {
+            assert_eq!(measure(&mut arr.clone(), sort).comparisons, 0);
+        }
+
+        // small buckets are finished with insertion sort, which doesn't count here
+        let mut words: Vec<String> = arr.iter().map(|x| x.to_string()).collect();
+        assert_eq!(measure(&mut words, radix_sort_bytes).comparisons, 0);
+        let mut floats: Vec<f64> = arr.iter().map(|&x| x as f64).collect();
+        assert_eq!(measure(&mut floats, |arr| bucket_sort(arr, 0.0, 100_000.0)).comparisons, 0);
+    }
+
+    #[test]
+    fn test_stats_every_sorter_reports() {
+        let mut arr: Vec<i32> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+
+        for sorter in all_integer_sorters::<i32>() {
+            let mut arr_clone = arr.clone();
+            let stats = sorter.sort_with_stats(&mut arr_clone);
+            assert_eq!(arr_clone, (0..300).collect::<Vec<_>>());
+            assert!(stats.swaps + stats.moves > 0, "{} recorded nothing", sorter.name());
+        }
+    }
+}
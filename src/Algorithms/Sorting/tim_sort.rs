@@ -0,0 +1,759 @@
+/***
+ * TimSort
+ * Stable, O(n log n) worst case, O(n) on already sorted or reversed input, O(n / 2) extra memory
+ *
+ * The slice is split into natural runs (ascending, or strictly descending which get reversed).
+ * Runs shorter than minrun (32..=64) are extended with binary insertion sort.
+ * Runs are pushed on a stack that is kept balanced by merging whenever
+ *   runs[n-3] <= runs[n-2] + runs[n-1]  or  runs[n-2] <= runs[n-1]
+ * (including the extra check on runs[n-4] from the 2015 fix), so merges stay between
+ * runs of similar size. Merges gallop (exponential search) once one run keeps
+ * winning, which makes merging long, barely interleaved runs close to a memcpy.
+ *
+ * Upsides:
+ * - Exploits existing order, ideal for partially sorted data like appended logs
+ * - Stable
+ * - Few comparisons, good for expensive comparators
+ *
+ * Downsides:
+ * - Complex, a lot of bookkeeping for small or random inputs
+ * - Needs a scratch buffer like every merge based sort
+ */
+
+use std::cmp::Ordering;
+use std::ptr;
+use std::slice;
+use super::partial_order;
+use super::sorter::Sorter;
+use super::stats::{counted, record_merge, record_writes, reverse, Scratch};
+
+// slices shorter than this are sorted with binary insertion sort alone
+const MIN_MERGE: usize = 64;
+// initial number of consecutive wins before a merge switches to galloping
+const MIN_GALLOP: usize = 7;
+
+pub fn timsort<T: PartialOrd>(arr: &mut [T]) {
+    timsort_by(arr, partial_order);
+}
+
+pub fn timsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut compare = counted(compare);
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+
+    if len < MIN_MERGE {
+        let run = count_run_and_make_ascending(arr, &mut is_less);
+        binary_insertion_sort(arr, run, &mut is_less);
+        return;
+    }
+
+    let mut state = RunStack::new(len);
+    let min_run = min_run_length(len);
+    let mut lo = 0;
+    while lo < len {
+        let mut run_len = count_run_and_make_ascending(&mut arr[lo..], &mut is_less);
+        // short natural run, extend it to min_run elements
+        if run_len < min_run {
+            let forced = usize::min(min_run, len - lo);
+            binary_insertion_sort(&mut arr[lo..lo + forced], run_len, &mut is_less);
+            run_len = forced;
+        }
+        state.runs.push(Run { start: lo, len: run_len });
+        state.merge_collapse(arr, &mut is_less);
+        lo += run_len;
+    }
+    state.merge_force_collapse(arr, &mut is_less);
+}
+
+pub fn timsort_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    F: FnMut(&T) -> K,
+    K: PartialOrd,
+{
+    timsort_by(arr, |a, b| partial_order(&key(a), &key(b)));
+}
+
+/*
+    minrun: take the six most significant bits of n, add one if any of the remaining
+    bits are set. n / minrun is then a power of two or slightly below one,
+    which keeps the final merges balanced.
+*/
+fn min_run_length(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+// length of the run starting at v[0], a strictly descending run is reversed in place
+fn count_run_and_make_ascending<T, F>(v: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    if len < 2 {
+        return len;
+    }
+
+    let mut end = 2;
+    if is_less(&v[1], &v[0]) {
+        while end < len && is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+        reverse(&mut v[..end]);
+    } else {
+        while end < len && !is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    }
+    end
+}
+
+// v[..sorted] is already sorted, binary search the slot for every following element
+fn binary_insertion_sort<T, F>(v: &mut [T], sorted: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in sorted.max(1)..v.len() {
+        // insert after equal elements to stay stable
+        let mut lo = 0;
+        let mut hi = i;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if is_less(&v[i], &v[mid]) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        if lo < i {
+            v[lo..=i].rotate_right(1);
+            record_writes(&v[lo], i - lo + 1);
+        }
+    }
+}
+
+/*
+    Galloping searches, both start at hint and double the step until they overshoot,
+    then binary search the last step. Cost is O(log d) where d is the distance to the answer.
+
+    gallop_left:  first index k with key <= a[k]  (a[..k] < key)
+    gallop_right: first index k with key <  a[k]  (a[..k] <= key)
+*/
+fn gallop_left<T, F>(key: &T, a: &[T], hint: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = a.len();
+    let mut last_ofs = 0;
+    let mut ofs = 1;
+    let (mut lo, mut hi);
+
+    if is_less(&a[hint], key) {
+        // answer is right of hint
+        let max_ofs = len - hint;
+        while ofs < max_ofs && is_less(&a[hint + ofs], key) {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        lo = hint + last_ofs + 1;
+        hi = hint + ofs;
+    } else {
+        // answer is at or left of hint
+        let max_ofs = hint + 1;
+        while ofs < max_ofs && !is_less(&a[hint - ofs], key) {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        lo = hint + 1 - ofs;
+        hi = hint - last_ofs;
+    }
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if is_less(&a[mid], key) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+fn gallop_right<T, F>(key: &T, a: &[T], hint: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = a.len();
+    let mut last_ofs = 0;
+    let mut ofs = 1;
+    let (mut lo, mut hi);
+
+    if is_less(key, &a[hint]) {
+        // answer is at or left of hint
+        let max_ofs = hint + 1;
+        while ofs < max_ofs && is_less(key, &a[hint - ofs]) {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        lo = hint + 1 - ofs;
+        hi = hint - last_ofs;
+    } else {
+        // answer is right of hint
+        let max_ofs = len - hint;
+        while ofs < max_ofs && !is_less(key, &a[hint + ofs]) {
+            last_ofs = ofs;
+            ofs = (ofs << 1) + 1;
+        }
+        ofs = ofs.min(max_ofs);
+        lo = hint + last_ofs + 1;
+        hi = hint + ofs;
+    }
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if is_less(key, &a[mid]) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct RunStack<T> {
+    buf: Vec<T>,       // scratch space, only ever holds moved out elements, len stays 0
+    _scratch: Scratch, // reports buf to the sort stats
+    runs: Vec<Run>,    // pending runs, bottom of the stack first
+    min_gallop: usize, // adapts per sort, lower when galloping pays off
+}
+
+/*
+    Same trick as merge sort: the buffered run is logically missing from the slice,
+    the hole remembers what is still buffered and where it belongs, and Drop moves it back.
+    Keeps every element in the slice exactly once even if the comparator panics.
+*/
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let remaining = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, remaining);
+        }
+    }
+}
+
+impl<T> RunStack<T> {
+    fn new(len: usize) -> Self {
+        RunStack {
+            buf: Vec::with_capacity(len / 2),
+            _scratch: Scratch::new::<T>(len / 2),
+            runs: Vec::new(),
+            min_gallop: MIN_GALLOP,
+        }
+    }
+
+    // restores the run stack invariants, merging from the top of the stack
+    fn merge_collapse<F>(&mut self, v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            let runs = &self.runs;
+            let mut at = n - 2;
+            if (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+                || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len)
+            {
+                if runs[n - 3].len < runs[n - 1].len {
+                    at = n - 3;
+                }
+            } else if runs[n - 2].len > runs[n - 1].len {
+                break;
+            }
+            self.merge_at(v, at, is_less);
+        }
+    }
+
+    // merges everything left on the stack once all runs are found
+    fn merge_force_collapse<F>(&mut self, v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            let mut at = n - 2;
+            if n >= 3 && self.runs[n - 3].len < self.runs[n - 1].len {
+                at = n - 3;
+            }
+            self.merge_at(v, at, is_less);
+        }
+    }
+
+    // merges runs[at] with runs[at + 1]
+    fn merge_at<F>(&mut self, v: &mut [T], at: usize, is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let Run { start: base1, len: mut len1 } = self.runs[at];
+        let Run { start: base2, len: mut len2 } = self.runs[at + 1];
+        self.runs[at].len = len1 + len2;
+        self.runs.remove(at + 1);
+
+        // run1 elements <= run2[0] are already in their final place
+        let skip = gallop_right(&v[base2], &v[base1..base1 + len1], 0, is_less);
+        let base1 = base1 + skip;
+        len1 -= skip;
+        if len1 == 0 {
+            return;
+        }
+
+        // run2 elements >= run1's last are already in their final place
+        len2 = gallop_left(&v[base1 + len1 - 1], &v[base2..base2 + len2], len2 - 1, is_less);
+        if len2 == 0 {
+            return;
+        }
+
+        let merged = &mut v[base1..base2 + len2];
+        record_merge(merged, len1);
+        unsafe {
+            if len1 <= len2 {
+                self.merge_lo(merged, len1, is_less);
+            } else {
+                self.merge_hi(merged, len1, is_less);
+            }
+        }
+    }
+
+    /*
+        Merges v[..len1] and v[len1..] front to back with run1 parked in the buffer.
+        Precondition (from merge_at): v[len1] < v[0] and v[len1 - 1] > v[last].
+    */
+    unsafe fn merge_lo<F>(&mut self, v: &mut [T], len1: usize, is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut len2 = v.len() - len1;
+        let v = v.as_mut_ptr();
+        let tmp = self.buf.as_mut_ptr();
+        ptr::copy_nonoverlapping(v, tmp, len1);
+
+        // gap in v is hole.dest..right, always as long as the buffered remainder
+        let mut hole = MergeHole { start: tmp, end: tmp.add(len1), dest: v };
+        let mut right = v.add(len1);
+        let mut min_gallop = self.min_gallop;
+
+        // first element of run2 is known to go first
+        ptr::copy_nonoverlapping(right, hole.dest, 1);
+        hole.dest = hole.dest.add(1);
+        right = right.add(1);
+        len2 -= 1;
+
+        'outer: while len2 > 0 && hole.len() > 1 {
+            let mut count1 = 0; // consecutive wins of run1
+            let mut count2 = 0; // consecutive wins of run2
+
+            // one element at a time until a run keeps winning
+            loop {
+                if is_less(&*right, &*hole.start) {
+                    ptr::copy_nonoverlapping(right, hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    right = right.add(1);
+                    len2 -= 1;
+                    count2 += 1;
+                    count1 = 0;
+                    if len2 == 0 {
+                        break 'outer;
+                    }
+                } else {
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    hole.start = hole.start.add(1);
+                    count1 += 1;
+                    count2 = 0;
+                    if hole.len() == 1 {
+                        break 'outer;
+                    }
+                }
+                if (count1 | count2) >= min_gallop {
+                    break;
+                }
+            }
+
+            // galloping, move whole blocks while it keeps paying off
+            loop {
+                let buffered = slice::from_raw_parts(hole.start, hole.len());
+                count1 = gallop_right(&*right, buffered, 0, is_less);
+                if count1 != 0 {
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, count1);
+                    hole.dest = hole.dest.add(count1);
+                    hole.start = hole.start.add(count1);
+                    if hole.len() <= 1 {
+                        break 'outer;
+                    }
+                }
+                ptr::copy_nonoverlapping(right, hole.dest, 1);
+                hole.dest = hole.dest.add(1);
+                right = right.add(1);
+                len2 -= 1;
+                if len2 == 0 {
+                    break 'outer;
+                }
+
+                let remaining = slice::from_raw_parts(right, len2);
+                count2 = gallop_left(&*hole.start, remaining, 0, is_less);
+                if count2 != 0 {
+                    // source and destination can overlap inside v
+                    ptr::copy(right, hole.dest, count2);
+                    hole.dest = hole.dest.add(count2);
+                    right = right.add(count2);
+                    len2 -= count2;
+                    if len2 == 0 {
+                        break 'outer;
+                    }
+                }
+                ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                hole.dest = hole.dest.add(1);
+                hole.start = hole.start.add(1);
+                if hole.len() == 1 {
+                    break 'outer;
+                }
+
+                min_gallop = min_gallop.saturating_sub(1);
+                if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                    break;
+                }
+            }
+            // penalize leaving gallop mode
+            min_gallop += 2;
+        }
+        self.min_gallop = min_gallop.max(1);
+
+        if hole.len() == 1 && len2 > 0 {
+            // the last buffered element is bigger than the rest of run2, shift run2 down first
+            ptr::copy(right, hole.dest, len2);
+            hole.dest = hole.dest.add(len2);
+            right = right.add(len2);
+        }
+        // into the buffer and back, plus every run2 element that had to shift
+        record_writes(tmp, len1);
+        record_writes(v, len1 + right.offset_from(v.add(len1)) as usize);
+        // dropping the hole moves whatever is still buffered into place
+    }
+
+    /*
+        Merges v[..len1] and v[len1..] back to front with run2 parked in the buffer.
+        Same preconditions as merge_lo.
+    */
+    unsafe fn merge_hi<F>(&mut self, v: &mut [T], len1: usize, is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let len2 = v.len() - len1;
+        let base = v.as_mut_ptr();
+        let tmp = self.buf.as_mut_ptr();
+        ptr::copy_nonoverlapping(base.add(len1), tmp, len2);
+
+        // remaining run1 is base..left_end, gap in v is left_end..out
+        let run1_end = base.add(len1);
+        let mut left_end = run1_end;
+        let mut out = base.add(len1 + len2);
+        let mut hole = MergeHole { start: tmp, end: tmp.add(len2), dest: left_end };
+        let mut len1 = len1;
+        let mut min_gallop = self.min_gallop;
+
+        // last element of run1 is known to go last
+        left_end = left_end.sub(1);
+        out = out.sub(1);
+        ptr::copy_nonoverlapping(left_end, out, 1);
+        hole.dest = left_end;
+        len1 -= 1;
+
+        'outer: while len1 > 0 && hole.len() > 1 {
+            let mut count1 = 0;
+            let mut count2 = 0;
+
+            loop {
+                if is_less(&*hole.end.sub(1), &*left_end.sub(1)) {
+                    left_end = left_end.sub(1);
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(left_end, out, 1);
+                    hole.dest = left_end;
+                    len1 -= 1;
+                    count1 += 1;
+                    count2 = 0;
+                    if len1 == 0 {
+                        break 'outer;
+                    }
+                } else {
+                    hole.end = hole.end.sub(1);
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(hole.end, out, 1);
+                    count2 += 1;
+                    count1 = 0;
+                    if hole.len() == 1 {
+                        break 'outer;
+                    }
+                }
+                if (count1 | count2) >= min_gallop {
+                    break;
+                }
+            }
+
+            loop {
+                let run1 = slice::from_raw_parts(base, len1);
+                count1 = len1 - gallop_right(&*hole.end.sub(1), run1, len1 - 1, is_less);
+                if count1 != 0 {
+                    left_end = left_end.sub(count1);
+                    out = out.sub(count1);
+                    ptr::copy(left_end, out, count1);
+                    hole.dest = left_end;
+                    len1 -= count1;
+                    if len1 == 0 {
+                        break 'outer;
+                    }
+                }
+                hole.end = hole.end.sub(1);
+                out = out.sub(1);
+                ptr::copy_nonoverlapping(hole.end, out, 1);
+                if hole.len() == 1 {
+                    break 'outer;
+                }
+
+                let buffered = slice::from_raw_parts(hole.start, hole.len());
+                count2 = hole.len() - gallop_left(&*left_end.sub(1), buffered, hole.len() - 1, is_less);
+                if count2 != 0 {
+                    hole.end = hole.end.sub(count2);
+                    out = out.sub(count2);
+                    ptr::copy_nonoverlapping(hole.end, out, count2);
+                    if hole.len() <= 1 {
+                        break 'outer;
+                    }
+                }
+                left_end = left_end.sub(1);
+                out = out.sub(1);
+                ptr::copy_nonoverlapping(left_end, out, 1);
+                hole.dest = left_end;
+                len1 -= 1;
+                if len1 == 0 {
+                    break 'outer;
+                }
+
+                min_gallop = min_gallop.saturating_sub(1);
+                if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                    break;
+                }
+            }
+            min_gallop += 2;
+        }
+        self.min_gallop = min_gallop.max(1);
+
+        if hole.len() == 1 && len1 > 0 {
+            // the first buffered element is smaller than the rest of run1, shift run1 up first
+            ptr::copy(base, out.sub(len1), len1);
+            hole.dest = base;
+            left_end = base;
+        }
+        record_writes(tmp, len2);
+        record_writes(left_end, len2 + run1_end.offset_from(left_end) as usize);
+        // dropping the hole moves whatever is still buffered into place
+    }
+}
+
+impl<T> MergeHole<T> {
+    fn len(&self) -> usize {
+        unsafe { self.end.offset_from(self.start) as usize }
+    }
+}
+
+pub struct TimSort;
+
+impl<T: PartialOrd> Sorter<T> for TimSort {
+    fn name(&self) -> &'static str {
+        "Tim sort"
+    }
+
+    fn sort(&self, arr: &mut [T]) {
+        timsort(arr);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_tim_sort() {
+        let mut arr = [5, 4, 3, 2, 1];
+        timsort(&mut arr);
+        assert_eq!(arr, [1, 2, 3, 4, 5]);
+
+        let mut empty: [i32; 0] = [];
+        timsort(&mut empty);
+    }
+
+    // test 300 elements
+    #[test]
+    fn test_tim_sort_300() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+        timsort(&mut arr);
+        for i in 0..arr.len() - 1 {
+            assert!(arr[i] <= arr[i + 1]);
+        }
+    }
+
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(63), 63);
+        assert_eq!(min_run_length(64), 32);
+        assert_eq!(min_run_length(65), 33);
+        for n in 64..5000 {
+            let r = min_run_length(n);
+            assert!((32..=64).contains(&r), "{} -> {}", n, r);
+        }
+    }
+
+    #[test]
+    fn test_gallops() {
+        let a = [1, 2, 2, 2, 3, 5, 8, 8, 13];
+        let mut is_less = |x: &i32, y: &i32| x < y;
+        for hint in 0..a.len() {
+            assert_eq!(gallop_left(&2, &a, hint, &mut is_less), 1);
+            assert_eq!(gallop_right(&2, &a, hint, &mut is_less), 4);
+            assert_eq!(gallop_left(&8, &a, hint, &mut is_less), 6);
+            assert_eq!(gallop_right(&8, &a, hint, &mut is_less), 8);
+            assert_eq!(gallop_left(&0, &a, hint, &mut is_less), 0);
+            assert_eq!(gallop_right(&99, &a, hint, &mut is_less), 9);
+        }
+    }
+
+    #[test]
+    fn test_tim_sort_partially_sorted() {
+        let mut rng = thread_rng();
+        // long sorted stretches with a few random blocks appended, like a merged log
+        let mut arr: Vec<u32> = (0..4000).collect();
+        arr.extend((0..500).map(|_| rng.gen_range(0..4000)));
+        arr.extend((2000..6000).rev());
+        arr.extend(0..3000);
+
+        let mut expected = arr.clone();
+        expected.sort();
+        timsort(&mut arr);
+        assert_eq!(arr, expected);
+    }
+
+    #[test]
+    fn test_tim_sort_stable() {
+        let mut rng = thread_rng();
+        // few distinct keys forces long galloping stretches over equal elements
+        for distinct in [2, 10, 1000] {
+            let mut arr: Vec<(u32, usize)> = (0..5000).map(|i| (rng.gen_range(0..distinct), i)).collect();
+            // sort a prefix so the input mixes long runs and noise
+            arr[..2500].sort_by_key(|p| p.0);
+            for (i, p) in arr.iter_mut().enumerate() {
+                p.1 = i;
+            }
+
+            timsort_by_key(&mut arr, |p| p.0);
+            for w in arr.windows(2) {
+                assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1), "{:?}", w);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tim_sort_by_descending() {
+        let mut arr: Vec<_> = (0..3000).collect();
+        arr.shuffle(&mut thread_rng());
+        timsort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, (0..3000).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_tim_sort_random_lengths() {
+        let mut rng = thread_rng();
+        for _ in 0..200 {
+            let len = rng.gen_range(0..600);
+            let mut arr: Vec<i32> = (0..len).map(|_| rng.gen_range(-50..50)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            timsort(&mut arr);
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_tim_sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        // runs of sorted data plus noise, so panics land in both merge directions and while galloping
+        let mut input: Vec<String> = (0..600).map(|i| format!("{:03}", i)).collect();
+        input[..300].shuffle(&mut thread_rng());
+        input[400..].reverse();
+
+        let mut total = 0;
+        timsort_by(&mut input.clone(), |a, b| {
+            total += 1;
+            a.cmp(b)
+        });
+
+        for stop in (1..total).step_by(97) {
+            let mut arr = input.clone();
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                timsort_by(&mut arr, |a, b| {
+                    calls += 1;
+                    if calls == stop {
+                        panic!("comparator gave up");
+                    }
+                    a.cmp(b)
+                });
+            }));
+            assert!(result.is_err());
+
+            // every element is still there exactly once
+            arr.sort();
+            let expected: Vec<String> = (0..600).map(|i| format!("{:03}", i)).collect();
+            assert_eq!(arr, expected);
+        }
+    }
+
+    // test 100000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn test_tim_sort_100000() {
+        let mut arr: Vec<_> = (0..100000).collect();
+        arr.shuffle(&mut thread_rng());
+        timsort(&mut arr);
+        assert_eq!(arr, (0..100000).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,282 @@
+/***
+ * Sort Trace
+ * Step by step event stream of a sort, for animations and for asserting on what a sort did
+ *
+ * trace(arr, |arr| quicksort(arr), |event| ...) runs the sort and hands every step to the
+ * observer as it happens, trace_events collects them into a Vec instead.
+ * Indices are positions in the slice passed to trace, also for steps taken on sub-slices.
+ *
+ * Rides on the same hooks as stats.rs, which means:
+ * - Compare only shows up when both operands live in the slice. Merges compare against their
+ *   scratch buffer and top_k against items that aren't in any slice, those have no index.
+ * - Writes into scratch buffers are counted in the stats but have no event, a merge shows up as
+ *   Merge followed by a Write for every slot it filled.
+ * - The non-comparison sorts move (key, index) pairs around and finish with swaps in the slice.
+ * - Zero sized types have no addresses to tell apart, only Partition and Merge are reported.
+ *
+ * The observer runs on a scoped thread of its own and the hooks send it the events through a
+ * channel, so it only has to be Send and may borrow from the caller. It sees the events in the
+ * order they happened, shortly after. Parallel sorts report from every worker into the same
+ * channel, events from different workers interleave but touch disjoint parts of the slice.
+ * A panicking observer stops getting events, its panic is raised once the sort is done.
+ */
+
+use std::mem;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use super::stats::{Counters, SortStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortEvent {
+    Compare(usize, usize),                                        // arr[i] compared to arr[j]
+    Swap(usize, usize),                                           // arr[i] and arr[j] swapped
+    Write(usize),                                                 // arr[i] overwritten by a moved element
+    Partition { start: usize, end: usize, lt: usize, gt: usize }, // arr[start..end] split around a pivot: [lt, gt) == pivot,
+                                                                  // nothing bigger in [start, lt), nothing smaller in [gt, end)
+    Merge { start: usize, mid: usize, end: usize },               // arr[start..mid] and arr[mid..end] about to be merged
+}
+
+// runs sort on arr and calls observer with every step, returns the same stats as measure
+pub fn trace<T, S, O>(arr: &mut [T], sort: S, mut observer: O) -> SortStats
+where
+    S: FnOnce(&mut [T]),
+    O: FnMut(SortEvent) + Send,
+{
+    let (events, received) = mpsc::channel();
+    let tracer = Tracer {
+        base: arr.as_ptr() as usize,
+        elem_size: mem::size_of::<T>(),
+        len: arr.len(),
+        events,
+    };
+    thread::scope(|s| {
+        // ends once record has dropped the tracer and with it the last sender
+        s.spawn(move || received.into_iter().for_each(&mut observer));
+        Counters::new(Some(tracer)).record(arr, sort)
+    })
+}
+
+// runs sort on arr and returns every step in order
+pub fn trace_events<T, S>(arr: &mut [T], sort: S) -> Vec<SortEvent>
+where
+    S: FnOnce(&mut [T]),
+{
+    let mut events = Vec::new();
+    trace(arr, sort, |event| events.push(event));
+    events
+}
+
+/*
+    The traced slice (as an address range) and where its events go.
+    The hooks only see pointers, index_of maps them back to positions.
+*/
+pub(crate) struct Tracer {
+    base: usize,
+    elem_size: usize,
+    len: usize,
+    events: Sender<SortEvent>, // to the observer thread of trace
+}
+
+impl Tracer {
+    // index of the element at ptr, None if ptr isn't an element of the traced slice
+    pub(crate) fn index_of<U>(&self, ptr: *const U) -> Option<usize> {
+        if self.elem_size == 0 || mem::size_of::<U>() != self.elem_size {
+            return None;
+        }
+        let offset = (ptr as usize).checked_sub(self.base)?;
+        let index = offset / self.elem_size;
+        if offset % self.elem_size != 0 || index >= self.len {
+            return None;
+        }
+        Some(index)
+    }
+
+    pub(crate) fn emit(&self, event: SortEvent) {
+        // fails only if the observer panicked, trace re-raises that when the sort is done
+        let _ = self.events.send(event);
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bubble_sort::bubblesort;
+    use super::super::insertion_sort::{insertionsort, insertionsort_by_key};
+    use super::super::merge_sort::mergesort;
+    use super::super::parallel_sort::{par_quicksort_with, ParallelConfig};
+    use super::super::quick_sort::{quicksort, quicksort_three_way, PivotStrategy};
+    use super::super::sorter::all_integer_sorters;
+    use super::super::stats::measure;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    // applies the Swap events of a trace to arr
+    fn replay_swaps(arr: &mut [i32], events: &[SortEvent]) {
+        for event in events {
+            if let SortEvent::Swap(i, j) = *event {
+                arr.swap(i, j);
+            }
+        }
+    }
+
+    fn shuffled(len: i32) -> Vec<i32> {
+        let mut arr: Vec<i32> = (0..len).collect();
+        arr.shuffle(&mut thread_rng());
+        arr
+    }
+
+    #[test]
+    fn test_trace_insertion_sort_sorted() {
+        let mut arr: Vec<i32> = (0..100).collect();
+        let events = trace_events(&mut arr, insertionsort);
+        assert!(events.iter().all(|e| !matches!(e, SortEvent::Swap(..))));
+        let expected: Vec<SortEvent> = (1..100).map(|i| SortEvent::Compare(i - 1, i)).collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn test_trace_matches_stats() {
+        let arr = shuffled(200);
+        let mut traced = arr.clone();
+        let events = trace_events(&mut traced, bubblesort);
+        let mut measured = arr.clone();
+        let stats = measure(&mut measured, bubblesort);
+
+        let count = |f: fn(&SortEvent) -> bool| events.iter().filter(|e| f(e)).count() as u64;
+        assert_eq!(count(|e| matches!(e, SortEvent::Compare(..))), stats.comparisons);
+        assert_eq!(count(|e| matches!(e, SortEvent::Swap(..))), stats.swaps);
+        assert_eq!(traced, measured);
+    }
+
+    #[test]
+    fn test_trace_replay_swaps() {
+        let arr = shuffled(300);
+        // every sort that only ever swaps inside the slice can be replayed from its trace
+        for sorter in all_integer_sorters::<i32>() {
+            let mut sorted = arr.clone();
+            let events = trace_events(&mut sorted, |arr| sorter.sort(arr));
+            if events.iter().any(|e| matches!(e, SortEvent::Write(_))) {
+                continue;
+            }
+            let mut replayed = arr.clone();
+            replay_swaps(&mut replayed, &events);
+            assert_eq!(replayed, sorted, "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn test_trace_partitions() {
+        let mut arr: Vec<i32> = shuffled(500).into_iter().map(|x| x % 50).collect();
+        let input = arr.clone();
+        let sorts: [fn(&mut [i32]); 2] = [quicksort, |arr| quicksort_three_way(arr, PivotStrategy::Random)];
+        for sort in sorts {
+            arr.copy_from_slice(&input);
+            let events = trace_events(&mut arr, sort);
+            assert!(events.iter().any(|e| matches!(e, SortEvent::Partition { .. })));
+
+            // at every partition the replayed slice holds what the event claims
+            let mut replayed = input.clone();
+            for event in events {
+                match event {
+                    SortEvent::Swap(i, j) => replayed.swap(i, j),
+                    SortEvent::Partition { start, end, lt, gt } => {
+                        assert!(start <= lt && lt < gt && gt <= end && end <= replayed.len());
+                        let pivot = replayed[lt];
+                        assert!(replayed[start..lt].iter().all(|&x| x <= pivot));
+                        assert!(replayed[lt..gt].iter().all(|&x| x == pivot));
+                        assert!(replayed[gt..end].iter().all(|&x| x >= pivot));
+                    }
+                    _ => {}
+                }
+            }
+            assert_eq!(replayed, arr);
+        }
+    }
+
+    #[test]
+    fn test_trace_merges() {
+        let mut arr = shuffled(1000);
+        let events = trace_events(&mut arr, mergesort);
+        assert_eq!(arr, (0..1000).collect::<Vec<_>>());
+
+        let merges: Vec<SortEvent> = events.iter().copied().filter(|e| matches!(e, SortEvent::Merge { .. })).collect();
+        for merge in merges.iter() {
+            if let SortEvent::Merge { start, mid, end } = *merge {
+                assert!(start < mid && mid < end && end <= 1000);
+            }
+        }
+        // the last merge covers the whole slice
+        assert!(matches!(merges.last(), Some(SortEvent::Merge { start: 0, end: 1000, .. })));
+        assert!(events.iter().all(|e| match *e {
+            SortEvent::Write(i) => i < 1000,
+            _ => true,
+        }));
+    }
+
+    #[test]
+    fn test_trace_callback() {
+        let mut arr = shuffled(100);
+        let mut swaps = 0;
+        let stats = trace(&mut arr, bubblesort, |event| {
+            if let SortEvent::Swap(..) = event {
+                swaps += 1;
+            }
+        });
+        assert_eq!(swaps, stats.swaps);
+        assert_eq!(arr, (0..100).collect::<Vec<_>>());
+
+        // by_key sorts are traced like the plain ones, reversing compares and swaps every pair
+        let events = trace_events(&mut arr, |arr| insertionsort_by_key(arr, |x| -x));
+        let compares = events.iter().filter(|e| matches!(e, SortEvent::Compare(..))).count();
+        assert_eq!(compares, 100 * 99 / 2);
+        assert_eq!(events.len(), 100 * 99);
+    }
+
+    #[test]
+    fn test_trace_parallel_and_nested() {
+        let arr = shuffled(4000);
+        let mut sorted = arr.clone();
+        let events = trace_events(&mut sorted, |arr| par_quicksort_with(arr, ParallelConfig::new(4, 100)));
+        let mut replayed = arr.clone();
+        replay_swaps(&mut replayed, &events);
+        assert_eq!(replayed, sorted);
+
+        // an observer sorting on its own doesn't end up in the trace
+        let arr = shuffled(50);
+        let mut count = 0;
+        trace(&mut arr.clone(), insertionsort, |_| {
+            let mut other = [3, 2, 1];
+            insertionsort(&mut other);
+            count += 1;
+        });
+        assert_eq!(count, trace_events(&mut arr.clone(), insertionsort).len());
+    }
+
+    #[test]
+    fn test_trace_panicking_observer() {
+        let mut arr = shuffled(100);
+        let traced = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            trace(&mut arr, bubblesort, |_| panic!("observer gave up"));
+        }));
+        assert!(traced.is_err());
+        // the sort itself ran to the end
+        assert_eq!(arr, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_trace_sub_slices_and_zst() {
+        let mut arr = shuffled(100);
+        let events = trace_events(&mut arr, |arr| insertionsort(&mut arr[50..]));
+        assert!(events.iter().all(|e| match *e {
+            SortEvent::Compare(i, j) | SortEvent::Swap(i, j) => i >= 50 && j >= 50,
+            _ => false,
+        }));
+
+        let mut units = [(); 10];
+        assert!(trace_events(&mut units, quicksort).iter().all(|e| matches!(e, SortEvent::Partition { .. })));
+    }
+}
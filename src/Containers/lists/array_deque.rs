@@ -0,0 +1,843 @@
+/***
+ * ArrayDeque
+ * Double ended queue on a ring buffer
+ * push_front/push_back/pop_front/pop_back: O(1) amortized
+ * get/index: O(1)
+ * rotate_left/rotate_right: O(min(n, len - n)), make_contiguous: O(capacity)
+ *
+ * The elements live in one buffer starting at head and wrap around its end, so both ends
+ * grow and shrink without shifting anything. as_slices returns the two halves,
+ * make_contiguous moves them together into one slice.
+ *
+ * push_back / push_front double the capacity when full. A bounded deque keeps its capacity,
+ * once full push_*_overwrite evicts the element at the other end (a sliding window over
+ * the last n items) and try_push_* hands the item back. Plain push_* panics on a full
+ * bounded deque, like indexing past the end.
+ *
+ * Upsides:
+ * - O(1) at both ends, unlike ArrayList::prepend
+ * - One contiguous allocation, CPU cache friendly
+ * - Bounded mode never allocates after construction
+ *
+ * Downsides:
+ * - Inserting in the middle still shifts elements
+ * - The contents are only a single slice after make_contiguous
+ * - Growth is expensive (requires copying to new array)
+ */
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ops::{Index, IndexMut};
+use std::ptr;
+use super::array_list::allocate;
+
+// smallest capacity an unbounded deque grows to
+const MIN_CAPACITY: usize = 4;
+
+pub struct ArrayDeque<T> {
+    buf: Box<[MaybeUninit<T>]>, // the len slots from head on (wrapping) are initialized
+    head: usize,                // physical index of the front element
+    len: usize,
+    bound: Option<usize>,       // fixed capacity of a bounded deque
+}
+
+// try_push_back / try_push_front on a full bounded deque, holds the rejected item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayDequeFull<T>(pub T);
+
+impl<T> ArrayDequeFull<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Display for ArrayDequeFull<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deque is full")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ArrayDequeFull<T> {}
+
+pub struct ArrayDequeIter<'a, T> {
+    deque: &'a ArrayDeque<T>,
+    front: usize, // logical indices, front..back are not yielded yet
+    back: usize,
+}
+
+pub struct ArrayDequeIterMut<'a, T> {
+    front: std::slice::IterMut<'a, T>, // the two halves of as_mut_slices
+    back: std::slice::IterMut<'a, T>,
+}
+
+pub struct ArrayDequeIntoIter<T> {
+    deque: ArrayDeque<T>,
+}
+
+impl<T> ArrayDeque<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArrayDeque {
+            buf: allocate(capacity),
+            head: 0,
+            len: 0,
+            bound: None,
+        }
+    }
+
+    // never holds more than capacity elements and never reallocates
+    pub fn bounded(capacity: usize) -> Self {
+        ArrayDeque {
+            buf: allocate(capacity),
+            head: 0,
+            len: 0,
+            bound: Some(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // elements it can hold before growing, the fixed limit for bounded deques
+    pub fn capacity(&self) -> usize {
+        match self.bound {
+            Some(capacity) => capacity,
+            None => self.buf.len(),
+        }
+    }
+
+    // only a bounded deque is ever full, an unbounded one grows instead
+    pub fn is_full(&self) -> bool {
+        matches!(self.bound, Some(capacity) if self.len == capacity)
+    }
+
+    // grows if needed, panics if the deque is bounded and full
+    pub fn push_back(&mut self, item: T) {
+        assert!(self.make_room(), "push_back on a full bounded ArrayDeque");
+        self.write_back(item);
+    }
+
+    pub fn push_front(&mut self, item: T) {
+        assert!(self.make_room(), "push_front on a full bounded ArrayDeque");
+        self.write_front(item);
+    }
+
+    // Err with the item if the deque is bounded and full, an unbounded one always takes it
+    pub fn try_push_back(&mut self, item: T) -> Result<(), ArrayDequeFull<T>> {
+        if !self.make_room() {
+            return Err(ArrayDequeFull(item));
+        }
+        self.write_back(item);
+        Ok(())
+    }
+
+    pub fn try_push_front(&mut self, item: T) -> Result<(), ArrayDequeFull<T>> {
+        if !self.make_room() {
+            return Err(ArrayDequeFull(item));
+        }
+        self.write_front(item);
+        Ok(())
+    }
+
+    /*
+        A full bounded deque evicts its front to make room and returns it, one bounded to zero
+        elements returns item itself. Otherwise the same as push_back, returns None.
+    */
+    pub fn push_back_overwrite(&mut self, item: T) -> Option<T> {
+        if self.make_room() {
+            self.write_back(item);
+            return None;
+        }
+        if self.len == 0 {
+            return Some(item);
+        }
+        let front = self.pop_front();
+        self.write_back(item);
+        front
+    }
+
+    // push_back_overwrite at the other end, evicts the back
+    pub fn push_front_overwrite(&mut self, item: T) -> Option<T> {
+        if self.make_room() {
+            self.write_front(item);
+            return None;
+        }
+        if self.len == 0 {
+            return Some(item);
+        }
+        let back = self.pop_back();
+        self.write_front(item);
+        back
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        // the slot leaves the live range before it is read, so it is read exactly once
+        let item = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.wrap_add(self.head, 1);
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.physical(self.len);
+        Some(unsafe { self.buf[slot].assume_init_read() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    // index 0 is the front
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(unsafe { self.buf[self.physical(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.physical(index);
+        Some(unsafe { self.buf[slot].assume_init_mut() })
+    }
+
+    pub fn clear(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        let (front, back) = (front as *mut [T], back as *mut [T]);
+        // len first, a panicking Drop must not lead to a second drop of the same element
+        self.len = 0;
+        self.head = 0;
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+
+    // moves the first n elements to the back, n is taken modulo len
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        let n = n % self.len;
+        if n <= self.len - n {
+            for _ in 0..n {
+                self.move_front_to_back();
+            }
+        } else {
+            for _ in 0..self.len - n {
+                self.move_back_to_front();
+            }
+        }
+    }
+
+    // moves the last n elements to the front, n is taken modulo len
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        let n = n % self.len;
+        self.rotate_left(self.len - n);
+    }
+
+    // the elements front to back, the second slice is empty unless they wrap around
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.split();
+        unsafe {
+            let ptr = self.buf.as_ptr() as *const T;
+            (
+                std::slice::from_raw_parts(ptr.add(self.head), front),
+                std::slice::from_raw_parts(ptr, back),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.split();
+        unsafe {
+            let ptr = self.buf.as_mut_ptr() as *mut T;
+            (
+                std::slice::from_raw_parts_mut(ptr.add(self.head), front),
+                std::slice::from_raw_parts_mut(ptr, back),
+            )
+        }
+    }
+
+    // moves the elements into one slice, front first. Free if they don't wrap
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.split().1 > 0 {
+            // rotating the uninit slots along is harmless, they are never read
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    pub fn iter(&self) -> ArrayDequeIter<'_, T> {
+        self.into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> ArrayDequeIterMut<'_, T> {
+        self.into_iter()
+    }
+
+    // true if there is a free slot for a push, grows an unbounded deque to get one
+    fn make_room(&mut self) -> bool {
+        match self.bound {
+            Some(capacity) => self.len < capacity,
+            None => {
+                if self.len == self.buf.len() {
+                    self.grow();
+                }
+                true
+            }
+        }
+    }
+
+    // needs a free slot
+    fn write_back(&mut self, item: T) {
+        let slot = self.physical(self.len);
+        self.buf[slot].write(item);
+        self.len += 1;
+    }
+
+    // needs a free slot
+    fn write_front(&mut self, item: T) {
+        self.head = self.wrap_sub(self.head, 1);
+        self.buf[self.head].write(item);
+        self.len += 1;
+    }
+
+    // pop_front + write_back without the checks, there is always a free slot after the pop
+    fn move_front_to_back(&mut self) {
+        if let Some(item) = self.pop_front() {
+            self.write_back(item);
+        }
+    }
+
+    fn move_back_to_front(&mut self) {
+        if let Some(item) = self.pop_back() {
+            self.write_front(item);
+        }
+    }
+
+    // doubles the buffer and unwraps the elements to the start of it
+    fn grow(&mut self) {
+        // only overflows for zero sized types after usize::MAX pushes
+        assert!(self.len < usize::MAX, "ArrayDeque capacity overflow");
+        let capacity = self.buf.len().saturating_mul(2).max(MIN_CAPACITY);
+        let mut buf = allocate(capacity);
+        let (front, back) = self.split();
+        // moves the live elements, the old slots are never read or dropped again
+        unsafe {
+            let old = self.buf.as_ptr();
+            let new = buf.as_mut_ptr();
+            ptr::copy_nonoverlapping(old.add(self.head), new, front);
+            ptr::copy_nonoverlapping(old, new.add(front), back);
+        }
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    // lengths of the part from head to the end of the buffer and of the wrapped part
+    fn split(&self) -> (usize, usize) {
+        let to_end = self.buf.len() - self.head;
+        if self.len <= to_end {
+            (self.len, 0)
+        } else {
+            (to_end, self.len - to_end)
+        }
+    }
+
+    // buffer slot of logical index i
+    fn physical(&self, i: usize) -> usize {
+        self.wrap_add(self.head, i)
+    }
+
+    // slot + n around the buffer without overflowing, even for the usize::MAX buffers of zero sized types
+    fn wrap_add(&self, slot: usize, n: usize) -> usize {
+        let to_end = self.buf.len() - slot;
+        if n < to_end {
+            slot + n
+        } else {
+            n - to_end
+        }
+    }
+
+    fn wrap_sub(&self, slot: usize, n: usize) -> usize {
+        if n <= slot {
+            slot - n
+        } else {
+            self.buf.len() - (n - slot)
+        }
+    }
+}
+
+impl<T> Drop for ArrayDeque<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for ArrayDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for ArrayDeque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index {} is out of bounds for length {}", index, self.len),
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for ArrayDeque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index {} is out of bounds for length {}", index, len),
+        }
+    }
+}
+
+impl<T: Clone> Clone for ArrayDeque<T> {
+    fn clone(&self) -> Self {
+        let mut deque = match self.bound {
+            Some(capacity) => ArrayDeque::bounded(capacity),
+            None => ArrayDeque::with_capacity(self.len),
+        };
+        for item in self {
+            // same length as self, so it always fits
+            deque.write_back(item.clone());
+        }
+        deque
+    }
+}
+
+// equal when the elements are, in order. Capacity, bound and where the buffer wraps don't matter
+impl<T: PartialEq> PartialEq for ArrayDeque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ArrayDeque<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for ArrayDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for ArrayDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = ArrayDeque::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+// push_back for every item, panics if a bounded deque fills up
+impl<T> Extend<T> for ArrayDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArrayDequeIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        let item = self.deque.get(self.front);
+        self.front += 1;
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayDequeIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.deque.get(self.back)
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayDequeIter<'_, T> {}
+
+impl<'a, T> Iterator for ArrayDequeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayDequeIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayDequeIterMut<'_, T> {}
+
+impl<T> Iterator for ArrayDequeIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayDequeIntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayDequeIntoIter<T> {}
+
+impl<'a, T> IntoIterator for &'a ArrayDeque<T> {
+    type Item = &'a T;
+    type IntoIter = ArrayDequeIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ArrayDequeIter {
+            deque: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArrayDeque<T> {
+    type Item = &'a mut T;
+    type IntoIter = ArrayDequeIterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let (front, back) = self.as_mut_slices();
+        ArrayDequeIterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+}
+
+impl<T> IntoIterator for ArrayDeque<T> {
+    type Item = T;
+    type IntoIter = ArrayDequeIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ArrayDequeIntoIter { deque: self }
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::lists::test_helpers::DropCounter;
+    use std::cell::Cell;
+
+    fn contents<T: Clone>(deque: &ArrayDeque<T>) -> Vec<T> {
+        deque.iter().cloned().collect()
+    }
+
+    #[test]
+    fn push_and_pop_both_ends() {
+        let mut deque = ArrayDeque::new();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+
+        for i in 0..5 {
+            deque.push_back(i);
+            deque.push_front(-i - 1);
+        }
+        assert_eq!(deque.len(), 10);
+        assert_eq!(contents(&deque), [-5, -4, -3, -2, -1, 0, 1, 2, 3, 4]);
+        assert_eq!(deque.front(), Some(&-5));
+        assert_eq!(deque.back(), Some(&4));
+
+        assert_eq!(deque.pop_front(), Some(-5));
+        assert_eq!(deque.pop_back(), Some(4));
+        *deque.front_mut().unwrap() = 40;
+        *deque.back_mut().unwrap() = 30;
+        assert_eq!(contents(&deque), [40, -3, -2, -1, 0, 1, 2, 30]);
+    }
+
+    #[test]
+    fn wraps_and_grows() {
+        let mut deque = ArrayDeque::with_capacity(4);
+        // move head around the buffer a few times before growing
+        for i in 0..10 {
+            deque.push_back(i);
+            assert_eq!(deque.pop_front(), Some(i));
+        }
+        for i in 0..4 {
+            deque.push_back(i);
+        }
+        assert_eq!(deque.capacity(), 4);
+        assert_ne!(deque.as_slices().1.len(), 0);
+
+        deque.push_front(-1);
+        assert_eq!(deque.capacity(), 8);
+        for i in 4..100 {
+            deque.push_back(i);
+        }
+        assert_eq!(contents(&deque), (-1..100).collect::<Vec<_>>());
+        for i in 0..50 {
+            assert_eq!(deque[i], i as i32 - 1);
+            deque[i] *= 2;
+        }
+        assert_eq!(deque.get(101), None);
+        assert_eq!(deque[49], 96);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let mut deque = ArrayDeque::with_capacity(8);
+        deque.push_back(1);
+        let _ = deque[1];
+    }
+
+    #[test]
+    fn bounded_overwrite() {
+        let mut window = ArrayDeque::bounded(3);
+        assert_eq!(window.push_back_overwrite(1), None);
+        assert_eq!(window.push_back_overwrite(2), None);
+        assert_eq!(window.push_back_overwrite(3), None);
+        assert!(window.is_full());
+        assert_eq!(window.push_back_overwrite(4), Some(1));
+        assert_eq!(window.push_back_overwrite(5), Some(2));
+        assert_eq!(contents(&window), [3, 4, 5]);
+        assert_eq!(window.push_front_overwrite(0), Some(5));
+        assert_eq!(contents(&window), [0, 3, 4]);
+        assert_eq!(window.capacity(), 3);
+
+        let mut none = ArrayDeque::bounded(0);
+        assert_eq!(none.push_back_overwrite(1), Some(1));
+        assert!(none.is_empty());
+
+        // unbounded deques grow instead of evicting
+        let mut deque = ArrayDeque::with_capacity(1);
+        assert_eq!(deque.push_back_overwrite(1), None);
+        assert_eq!(deque.push_front_overwrite(0), None);
+        assert_eq!(contents(&deque), [0, 1]);
+    }
+
+    #[test]
+    fn bounded_try_push() {
+        let mut deque = ArrayDeque::bounded(2);
+        assert_eq!(deque.try_push_back("a"), Ok(()));
+        assert_eq!(deque.try_push_front("b"), Ok(()));
+        assert_eq!(deque.try_push_back("c"), Err(ArrayDequeFull("c")));
+        assert_eq!(deque.try_push_front("d").unwrap_err().into_inner(), "d");
+        assert_eq!(contents(&deque), ["b", "a"]);
+        deque.pop_back();
+        deque.push_back("e");
+        assert_eq!(deque.capacity(), 2);
+        assert_eq!(contents(&deque), ["b", "e"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "full bounded ArrayDeque")]
+    fn push_on_full_bounded_panics() {
+        let mut deque = ArrayDeque::bounded(2);
+        deque.extend([1, 2, 3]);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut deque: ArrayDeque<i32> = (0..10).collect();
+        deque.rotate_left(3);
+        assert_eq!(contents(&deque), [3, 4, 5, 6, 7, 8, 9, 0, 1, 2]);
+        deque.rotate_right(3);
+        assert_eq!(contents(&deque), (0..10).collect::<Vec<_>>());
+        deque.rotate_left(8);
+        assert_eq!(contents(&deque), [8, 9, 0, 1, 2, 3, 4, 5, 6, 7]);
+        deque.rotate_right(22);
+        assert_eq!(contents(&deque), [6, 7, 8, 9, 0, 1, 2, 3, 4, 5]);
+        deque.rotate_left(10);
+        assert_eq!(deque[0], 6);
+
+        let mut empty = ArrayDeque::<i32>::new();
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn slices_and_make_contiguous() {
+        let mut deque = ArrayDeque::with_capacity(8);
+        for i in 0..6 {
+            deque.push_back(i);
+        }
+        for i in 1..=2 {
+            deque.push_front(-i);
+        }
+        let (front, back) = deque.as_slices();
+        assert_eq!(front, [-2, -1]);
+        assert_eq!(back, [0, 1, 2, 3, 4, 5]);
+
+        let slice = deque.make_contiguous();
+        assert_eq!(slice, [-2, -1, 0, 1, 2, 3, 4, 5]);
+        slice.sort_by(|a, b| b.cmp(a));
+        assert_eq!(deque.as_slices(), (&[5, 4, 3, 2, 1, 0, -1, -2][..], &[][..]));
+        assert_eq!(deque.pop_front(), Some(5));
+        deque.push_back(9);
+        assert_eq!(deque.make_contiguous(), [4, 3, 2, 1, 0, -1, -2, 9]);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut deque = ArrayDeque::with_capacity(4);
+        for i in 0..4 {
+            deque.push_front(i);
+        }
+        assert_eq!(deque.iter().len(), 4);
+        assert_eq!(deque.iter().rev().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+
+        for x in deque.iter_mut() {
+            *x *= 10;
+        }
+        for x in &mut deque {
+            *x += 1;
+        }
+        assert_eq!(deque.iter_mut().next_back(), Some(&mut 1));
+
+        let mut iter = deque.clone().into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back(), Some(1));
+        assert_eq!(iter.collect::<Vec<_>>(), [31, 21, 11]);
+        assert_eq!(format!("{:?}", deque), "[31, 21, 11, 1]");
+    }
+
+    #[test]
+    fn clone_and_eq() {
+        let mut a = ArrayDeque::with_capacity(4);
+        let mut b = ArrayDeque::with_capacity(16);
+        for i in 0..4 {
+            a.push_back(i);
+            b.push_back(i);
+        }
+        a.rotate_left(2);
+        a.rotate_right(2);
+        assert_eq!(a, b);
+        b.push_back(4);
+        assert_ne!(a, b);
+
+        let bounded: ArrayDeque<i32> = {
+            let mut d = ArrayDeque::bounded(2);
+            d.extend([1, 2]);
+            d
+        };
+        let mut copy = bounded.clone();
+        assert_eq!(copy, bounded);
+        assert_eq!(copy.try_push_back(3), Err(ArrayDequeFull(3)));
+    }
+
+    #[test]
+    fn drops_exactly_live_elements() {
+        let drops = Cell::new(0);
+        let mut deque = ArrayDeque::with_capacity(2);
+        for _ in 0..20 {
+            deque.push_back(DropCounter(&drops));
+            deque.push_front(DropCounter(&drops));
+        }
+        assert_eq!(drops.get(), 0);
+        drop(deque.pop_front());
+        drop(deque.pop_back());
+        deque.rotate_left(7);
+        deque.make_contiguous();
+        assert_eq!(drops.get(), 2);
+        deque.clear();
+        assert_eq!(drops.get(), 40);
+
+        let mut window = ArrayDeque::bounded(3);
+        for _ in 0..10 {
+            drop(window.push_back_overwrite(DropCounter(&drops)));
+        }
+        assert_eq!(drops.get(), 47);
+        let mut iter = window.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(drops.get(), 50);
+    }
+
+    #[test]
+    fn zero_sized_types() {
+        let mut deque = ArrayDeque::new();
+        for _ in 0..1000 {
+            deque.push_front(());
+            deque.push_back(());
+        }
+        deque.rotate_left(10);
+        assert_eq!(deque.len(), 2000);
+        assert_eq!(deque.pop_back(), Some(()));
+        assert_eq!(deque.make_contiguous().len(), 1999);
+        assert_eq!(deque.iter().count(), 1999);
+
+        let mut bounded = ArrayDeque::bounded(2);
+        bounded.push_back(());
+        bounded.push_back(());
+        assert_eq!(bounded.try_push_back(()), Err(ArrayDequeFull(())));
+    }
+}
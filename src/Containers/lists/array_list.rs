@@ -3,7 +3,30 @@
  * Wrapper that uses arrays under the hood
  * append/pop/get has O(1)
  * prepend has O(n)
- * constructor specifies initial size
+ * constructor specifies initial size, new() doesn't allocate until the first append
+ *
+ * Growth follows a GrowthPolicy (doubling by default, 1.5x, a fixed increment or any
+ * closure), reserve / reserve_exact allocate up front and shrink_to_fit / shrink_to hand
+ * memory back, pop and clear never reallocate on their own. Every reallocation is
+ * counted in memory_stats().
+ *
+ * Storage is a boxed slice of MaybeUninit<T>, only the first length slots are live.
+ * Slots past length are never read or dropped, so T needs no Default and growing moves
+ * the elements bitwise instead of copying and dropping them.
+ * Zero sized types never allocate, their capacity is usize::MAX.
+ *
+ * Bulk edits (insert_slice, remove_range, drain, splice, retain, dedup, split_off ...) move
+ * every element at most once, O(n) instead of a loop of O(n) single inserts.
+ * Index and range arguments that don't fit the list return an ArrayListError.
+ *
+ * Iterators: iter (&T), iter_mut (&mut T) and into_iter (T, consumes the list), all three
+ * double ended and exact size, so rev, len, zip, collect ... work as usual.
+ *
+ * Derefs to [T], so every slice method and every sort in algorithms::sorting takes it
+ * directly: heapsort(&mut list), list.binary_search(&x), list[2..5].reverse() ...
+ *
+ * SmallArrayList (N elements inline, then the heap) and ArrayVecList (fixed capacity N,
+ * never allocates) have the same API, the element moving all three share is in raw_list.
  * 
  * Upsides:
  * - Speed: Arrays/slices make things really fast
@@ -20,263 +43,719 @@
  * - Growth speed decreases with size
  */
 
-//todo add iterator traits
-
-use std::fmt::Debug;
-use std::mem;
-use core::alloc::Layout;
-use std::alloc::alloc;
-use std::ptr;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::ptr;
+use std::slice::{self, SliceIndex};
+use std::sync::Arc;
+use super::raw_list::{check_insert_index, range_of, RawList};
+
+// smallest capacity the geometric policies grow an empty list to
+const MIN_CAPACITY: usize = 4;
+
 //fake array
 pub struct ArrayList<T> {
-    pub length: usize,
-    inner: Box<[T]>,
-    tail: usize,
+    length: usize,                // private, every unsafe block below trusts it
+    inner: Box<[MaybeUninit<T>]>, // inner[..length] is initialized, the rest is not
+    growth: GrowthPolicy,
+    stats: MemoryStats,
 }
 
+// how far the capacity goes when a full list needs room, never less than what was asked for
+#[derive(Clone, Default)]
+pub enum GrowthPolicy {
+    #[default]
+    Doubling,                                          // capacity * 2, at least MIN_CAPACITY
+    OneAndHalf,                                        // capacity * 1.5, at least MIN_CAPACITY
+    FixedIncrement(usize),                             // capacity + n, memory tight but O(n) appends
+    Custom(Arc<dyn Fn(usize) -> usize + Send + Sync>), // current capacity -> next capacity
+}
+
+// reallocations of one list since it was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub grows: u64,           // reallocations to a bigger buffer
+    pub shrinks: u64,         // reallocations to a smaller buffer
+    pub moved: u64,           // elements moved by those reallocations
+    pub capacity: usize,      // current capacity, in elements
+    pub peak_capacity: usize, // largest capacity so far
+    pub bytes: usize,         // current buffer size, capacity * size_of::<T>()
+    pub peak_bytes: usize,
+}
+
+// shared with SmallArrayList and ArrayVecList, like the drain
 pub struct ArrayListIterator<'a, T> {
-    current: usize,
-    list: &'a ArrayList<T>,
+    inner: slice::Iter<'a, T>,
 }
 
-impl<T: Default + Debug> ArrayList<T> {
-    pub fn new() -> ArrayList<T> {
-        let layout = Layout::array::<T>(5).unwrap();
-        let ptr = unsafe { alloc(layout) } as *mut T;
-        for i in 0..5 {
-            unsafe { ptr::write(ptr.add(i), T::default()) };
+pub struct ArrayListIterMut<'a, T> {
+    inner: slice::IterMut<'a, T>,
+}
+
+// owns the list, list.length is 0 so dropping the list only frees the buffer
+pub struct ArrayListIntoIter<T> {
+    list: ArrayList<T>,
+    front: usize, // inner[front..back] are the elements not yielded yet
+    back: usize,
+}
+
+// removes a range from the list, yields its elements and closes the gap when dropped
+pub struct ArrayListDrain<'a, T> {
+    list: RawList<'a, T>, // list.len() is the start of the range while draining
+    front: usize,         // next element to yield from the front
+    back: usize,          // one past the next element to yield from the back
+    tail: usize,          // first element after the range
+    tail_len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayListError {
+    IndexOutOfBounds { index: usize, len: usize }, // index past the end (or at it, where that isn't allowed)
+    InvalidRange { len: usize },                   // start > end or end > len
+    CapacityExceeded { capacity: usize },          // a fixed capacity list has no room left
+}
+
+impl fmt::Display for ArrayListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrayListError::IndexOutOfBounds { index, len } => write!(f, "index {} is out of bounds for length {}", index, len),
+            ArrayListError::InvalidRange { len } => write!(f, "range is decreasing or out of bounds for length {}", len),
+            ArrayListError::CapacityExceeded { capacity } => write!(f, "capacity of {} elements exceeded", capacity),
+        }
+    }
+}
+
+impl std::error::Error for ArrayListError {}
+
+impl GrowthPolicy {
+    // capacity after growing from capacity, before clamping to what is needed
+    pub fn next_capacity(&self, capacity: usize) -> usize {
+        match self {
+            GrowthPolicy::Doubling => capacity.saturating_mul(2).max(MIN_CAPACITY),
+            GrowthPolicy::OneAndHalf => capacity.saturating_add(capacity / 2).max(MIN_CAPACITY),
+            GrowthPolicy::FixedIncrement(n) => capacity.saturating_add(*n),
+            GrowthPolicy::Custom(next) => next(capacity),
         }
-        let slice = unsafe { Box::from_raw(std::slice::from_raw_parts_mut(ptr, 5)) };
-        ArrayList {
-            length: 0,
-            inner: slice,
-            tail: 0,
+    }
+}
+
+impl fmt::Debug for GrowthPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrowthPolicy::Doubling => write!(f, "Doubling"),
+            GrowthPolicy::OneAndHalf => write!(f, "OneAndHalf"),
+            GrowthPolicy::FixedIncrement(n) => write!(f, "FixedIncrement({})", n),
+            GrowthPolicy::Custom(_) => write!(f, "Custom(..)"),
         }
     }
+}
+
+impl<T> ArrayList<T> {
+    pub fn new() -> ArrayList<T> {
+        Self::with_capacity(0)
+    }
 
     pub fn with_capacity(capacity: usize) -> ArrayList<T> {
-        let layout = Layout::array::<T>(capacity).unwrap();
-        let ptr = unsafe { alloc(layout) } as *mut T;
-        for i in 0..capacity {
-            unsafe { ptr::write(ptr.add(i), T::default()) };
-        }
-        let slice = unsafe { Box::from_raw(std::slice::from_raw_parts_mut(ptr, capacity)) };
-        ArrayList {
-            length: 0,
-            inner: slice,
-            tail: 0,
-        }
-    }
-
-    fn grow_inner(&mut self) {
-        let len = self.inner.len();
-        let layout = Layout::array::<T>(len * 2).unwrap();
-        let ptr = unsafe { alloc(layout) } as *mut T;
-        let mut new_inner = unsafe { Box::from_raw(std::slice::from_raw_parts_mut(ptr, len * 2)) };
-        unsafe { 
-            ptr::copy(
-                self.inner.as_ptr(), 
-                new_inner.as_mut_ptr(), 
-                len
-            );
+        Self::with_growth_policy(capacity, GrowthPolicy::default())
+    }
+
+    pub fn with_growth_policy(capacity: usize, growth: GrowthPolicy) -> ArrayList<T> {
+        Self::from_parts(0, allocate(capacity), growth)
+    }
+
+    fn from_parts(length: usize, inner: Box<[MaybeUninit<T>]>, growth: GrowthPolicy) -> ArrayList<T> {
+        let mut list = ArrayList {
+            length,
+            inner,
+            growth,
+            stats: MemoryStats::default(),
+        };
+        list.update_stats();
+        list
+    }
+
+    // number of elements the list can hold before it has to grow
+    pub fn capacity(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn growth_policy(&self) -> &GrowthPolicy {
+        &self.growth
+    }
+
+    // applies from the next growth on, the current buffer stays as it is
+    pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+        self.growth = growth;
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.stats
+    }
+
+    // room for at least additional more elements, grown by the policy so appends stay amortized O(1)
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.needed(additional);
+        if needed > self.capacity() {
+            let next = self.growth.next_capacity(self.capacity());
+            self.reallocate(next.max(needed));
         }
-        self.inner = new_inner;
     }
 
-    pub fn append(&mut self, item: T) {
-        //println!("appending {item:?} to {self:?}");
-        self.length += 1;
-        if self.inner.len() <= self.length {
-            self.grow_inner();
-        } 
-        self.inner[self.tail] = item;
-        self.tail += 1;
+    // room for exactly additional more elements, no extra slack
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let needed = self.needed(additional);
+        if needed > self.capacity() {
+            self.reallocate(needed);
+        }
     }
 
-    pub fn prepend(&mut self, item: T) {
-        //println!("prepending {item:?} to {self:?}");
-        // If the array is empty, append the item instead, avoids shifting 
-        if self.length == 0 {
-            self.append(item);
-            return;
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    // lowers the capacity to min_capacity, or to len if that is bigger. Never grows
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let target = min_capacity.max(self.length);
+        if target < self.capacity() {
+            self.reallocate(target);
+        }
+    }
+
+    // length + additional
+    fn needed(&self, additional: usize) -> usize {
+        // only overflows for zero sized types after usize::MAX appends
+        self.length.checked_add(additional).expect("ArrayList capacity overflow")
+    }
+
+    // moves the live elements to a buffer of new_capacity, new_capacity >= length
+    fn reallocate(&mut self, new_capacity: usize) {
+        debug_assert!(new_capacity >= self.length);
+        let mut new_inner = allocate(new_capacity);
+        if new_inner.len() == self.capacity() {
+            return; // zero sized types, every buffer is usize::MAX long
+        }
+        // the old slots are uninit from here on and are never dropped
+        unsafe {
+            ptr::copy_nonoverlapping(self.inner.as_ptr(), new_inner.as_mut_ptr(), self.length);
         }
+        if new_inner.len() > self.capacity() {
+            self.stats.grows += 1;
+        } else {
+            self.stats.shrinks += 1;
+        }
+        self.stats.moved += self.length as u64;
+        self.inner = new_inner;
+        self.update_stats();
+    }
 
-        self.length += 1;
+    fn update_stats(&mut self) {
+        let capacity = self.capacity();
+        let bytes = capacity.saturating_mul(std::mem::size_of::<T>());
+        self.stats.capacity = capacity;
+        self.stats.peak_capacity = self.stats.peak_capacity.max(capacity);
+        self.stats.bytes = bytes;
+        self.stats.peak_bytes = self.stats.peak_bytes.max(bytes);
+    }
 
-        if self.inner.len() <= self.length {
-            self.grow_inner();
-        } 
-        //shift all items to the right 0
-        self.shift_all_right(0);
-        // front now empty, insert item
-        self.inner[0] = item;
-        // move tail back one
-        self.tail += 1;
+    pub fn append(&mut self, item: T) {
+        self.reserve(1);
+        self.raw().push(item);
+    }
+
+    pub fn prepend(&mut self, item: T) {
+        self.reserve(1);
+        // shift all items one to the right, front now free
+        self.raw().insert(0, item);
     }
 
     pub fn set(&mut self, index: usize, el: T) -> Option<T> {
         // replace mem at index with el and return replaced mem
-        if index >= self.length {
-            return None; //can't set an out of bounds index
+        let slot = self.get_mut(index)?; //can't set an out of bounds index
+        Some(std::mem::replace(slot, el))
+    }
+
+    // insert el at index, everything from index on moves one to the right. index == len appends
+    pub fn insert_at(&mut self, index: usize, el: T) -> Result<(), ArrayListError> {
+        check_insert_index(index, self.length)?;
+        self.reserve(1);
+        self.raw().insert(index, el);
+        Ok(())
+    }
+
+    // clones items in front of index, index == len appends them
+    pub fn insert_slice(&mut self, index: usize, items: &[T]) -> Result<(), ArrayListError>
+    where
+        T: Clone,
+    {
+        check_insert_index(index, self.length)?;
+        // cloned up front, a panicking clone leaves self untouched
+        self.insert_list(index, items.iter().cloned().collect());
+        Ok(())
+    }
+
+    pub fn extend_from_slice(&mut self, items: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(items.len());
+        for item in items {
+            self.append(item.clone());
         }
-        let item: T = mem::replace(&mut self.inner[index], el);
-        Some(item)
     }
 
-    pub fn insert_at( &mut self, index: usize, el: T)  {
-        // insert el at index and return replaced mem
-        if index >= self.length {
-            return ; //can't insert at out of bounds index
-        };
-        // shift all items to the right
-        self.shift_slice_right(index);
-        // insert el at just freed index
-        self.inner[index] = el;
-        // move tail back one and increment length
-        self.tail += 1;
-        self.length += 1;
+    // drops the elements in range and closes the gap
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) -> Result<(), ArrayListError> {
+        self.drain(range)?;
+        Ok(())
+    }
+
+    /*
+        Removes range from the list and yields its elements. Whatever the iterator didn't yield
+        is dropped with it, and the elements after the range move down to close the gap.
+        If the drain is leaked (mem::forget) the range and everything after it is leaked too,
+        the list itself stays valid.
+    */
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Result<ArrayListDrain<'_, T>, ArrayListError> {
+        let (start, end) = range_of(range, self.length)?;
+        Ok(ArrayListDrain::new(self.raw(), start, end))
+    }
+
+    // replaces range with replace_with and returns the removed elements
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Result<ArrayList<T>, ArrayListError>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let (start, end) = range_of(range, self.length)?;
+        let removed = self.drain(start..end)?.collect();
+        self.insert_list(start, replace_with.into_iter().collect());
+        Ok(removed)
+    }
+
+    // keeps the elements keep returns true for, in order. O(n), every kept element moves at most once
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, keep: F) {
+        self.raw().retain(keep);
+    }
+
+    // removes consecutive repeated elements, the first of every run stays
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    // same(a, b) is called with a later element and the last kept one, true drops a
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, same: F) {
+        self.raw().dedup_by(same);
+    }
+
+    // drops everything from len on, does nothing if the list is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        self.raw().truncate(len);
+    }
+
+    // moves the elements from at on into a new list, at == len returns an empty one
+    pub fn split_off(&mut self, at: usize) -> Result<ArrayList<T>, ArrayListError> {
+        check_insert_index(at, self.length)?;
+        let mut other = ArrayList::with_growth_policy(self.length - at, self.growth.clone());
+        self.raw().split_into(at, other.raw());
+        Ok(other)
+    }
+
+    // removes the element at index and moves the last one into its place, O(1), doesn't keep order
+    pub fn swap_remove(&mut self, index: usize) -> Result<T, ArrayListError> {
+        let len = self.length;
+        self.raw().swap_remove(index).ok_or(ArrayListError::IndexOutOfBounds { index, len })
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        if self.tail > 0 {
-            let item = std::mem::take(&mut self.inner[self.tail - 1]);
-            //println!("removing item {:?}", item);
-            self.tail -= 1;
-            self.length -= 1;
-            Some(item)
-        } else {
-            None
-        }
+        self.raw().pop()
     }
 
     pub fn pop_at(&mut self, index: usize) -> Option<T> {
-        if index < self.length {
-            let item = std::mem::take(&mut self.inner[index]);
-            self.shift_all_left(index);
-            self.tail -= 1;
-            self.length -= 1;
-            Some(item)
-        } else {
-            None //can't pop an out of bounds index
-        }
+        self.raw().remove(index) //can't pop an out of bounds index
     }
 
     pub fn get(&self, index: usize) -> Option<&T> {
-        match index < self.length {
-            true => Some(&self.inner[index]),
-            false => None,
-        }
+        self.as_slice().get(index)
     }
 
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        match index < self.length {
-            true => Some(&mut self.inner[index]),
-            false => None,
-        }
+        self.as_mut_slice().get_mut(index)
     }
 
-    fn shift_all_left(&mut self, start_index: usize) {
-        // Ensure there is enough space in the inner array
-        while self.inner.len() <= self.tail + 1 {
-            self.grow_inner();
-        }
-    
-        // If the array is empty, there's nothing to shift
-        if self.length == 0 {
-            return;
-        }
-    
-        // Shift elements to the left
-        for i in start_index..self.length {
-            self.inner[i] = std::mem::take(&mut self.inner[i + 1]);
-        }
+    // the live elements
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.inner.as_ptr() as *const T, self.length) }
     }
 
-    fn shift_slice_right(&mut self, start_index: usize) {
-        if self.length == self.inner.len() {
-            self.grow_inner();
-        }
-        if self.length == 0 {
-            return;
-        }
-        for i in (start_index..=self.length).rev() {
-            self.inner[i + 1] = std::mem::take(&mut self.inner[i]);
-        }
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.inner.as_mut_ptr() as *mut T, self.length) }
     }
 
-    fn shift_all_right(&mut self, offset: usize) {
-        // Ensure there is enough space in the inner array
-        while self.inner.len() <= self.tail + offset {
-            self.grow_inner();
-        }
-    
-        // If the array is empty, there's nothing to shift
-        if self.length == 0 {
-            return;
-        }
-    
-        // Shift elements to the right
-        for i in (offset..self.length).rev() {
-            self.inner[i + 1] = std::mem::take(&mut self.inner[i]);
-        }
+    // moves all of items in front of index, items ends up empty
+    fn insert_list(&mut self, index: usize, mut items: ArrayList<T>) {
+        self.reserve(items.length);
+        self.raw().insert_from(index, items.raw());
+    }
+
+    // the slots and length, for the element moving shared with the other lists
+    pub(super) fn raw(&mut self) -> RawList<'_, T> {
+        unsafe { RawList::new(&mut self.inner, &mut self.length) }
     }
 
     pub fn len(&self) -> usize {
         self.length
     }
 
+    pub fn iter(&self) -> ArrayListIterator<'_, T> {
+        self.into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> ArrayListIterMut<'_, T> {
+        self.into_iter()
+    }
+
+    // back to front
+    pub fn rev(&self) -> std::iter::Rev<ArrayListIterator<'_, T>> {
+        self.iter().rev()
+    }
+
+    // every run of size neighbouring elements, panics if size is 0
+    pub fn windows(&self, size: usize) -> slice::Windows<'_, T> {
+        self.as_slice().windows(size)
+    }
+
+    // size elements at a time, the last chunk may be shorter, panics if size is 0
+    pub fn chunks(&self, size: usize) -> slice::Chunks<'_, T> {
+        self.as_slice().chunks(size)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.length == 0
     }
 
     pub fn clear(&mut self) {
-        for i in 0..self.length {
-            self.inner[i] = T::default();
-        }
-        self.length = 0;
-        self.tail = 0;
+        self.truncate(0);
     }
 
 
 
 }
 
-impl<'a, T: Default + Debug> Iterator for ArrayListIterator<'a, T> {
+// capacity uninitialized slots, zero sized types get usize::MAX without allocating
+pub(super) fn allocate<T>(capacity: usize) -> Box<[MaybeUninit<T>]> {
+    let mut inner = Vec::with_capacity(capacity);
+    // MaybeUninit needs no initialization, and len == capacity keeps into_boxed_slice from reallocating
+    unsafe { inner.set_len(inner.capacity()) };
+    inner.into_boxed_slice()
+}
+
+impl<T> Drop for ArrayList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, T> ArrayListIterator<'a, T> {
+    pub(super) fn new(items: &'a [T]) -> Self {
+        ArrayListIterator { inner: items.iter() }
+    }
+}
+
+impl<'a, T> Iterator for ArrayListIterator<'a, T> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current < self.list.length {
-            let item = &self.list.inner[self.current];
-            self.current += 1;
-            Some(item)
-        } else {
-            None
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayListIterator<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayListIterator<'_, T> {}
+
+impl<'a, T> ArrayListIterMut<'a, T> {
+    pub(super) fn new(items: &'a mut [T]) -> Self {
+        ArrayListIterMut { inner: items.iter_mut() }
+    }
+}
+
+impl<'a, T> Iterator for ArrayListIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayListIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayListIterMut<'_, T> {}
+
+impl<T> Iterator for ArrayListIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        // every slot in front..back is read exactly once
+        let item = unsafe { self.list.inner[self.front].assume_init_read() };
+        self.front += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayListIntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(unsafe { self.list.inner[self.back].assume_init_read() })
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayListIntoIter<T> {}
+
+impl<T> Drop for ArrayListIntoIter<T> {
+    fn drop(&mut self) {
+        let ptr = self.list.inner.as_mut_ptr() as *mut T;
+        let rest = ptr::slice_from_raw_parts_mut(unsafe { ptr.add(self.front) }, self.back - self.front);
+        self.front = self.back;
+        unsafe { ptr::drop_in_place(rest) };
+    }
+}
+
+impl<'a, T> ArrayListDrain<'a, T> {
+    // drains list[start..end], start <= end <= list.len()
+    pub(super) fn new(mut list: RawList<'a, T>, start: usize, end: usize) -> Self {
+        let tail_len = list.len() - end;
+        // the range and the tail are owned by the drain until it is dropped
+        unsafe { list.set_len(start) };
+        ArrayListDrain {
+            list,
+            front: start,
+            back: end,
+            tail: end,
+            tail_len,
+        }
+    }
+}
+
+impl<T> Iterator for ArrayListDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        // past list.len(), so every slot is read exactly once
+        let item = unsafe { self.list.ptr().add(self.front).read() };
+        self.front += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayListDrain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(unsafe { self.list.ptr().add(self.back).read() })
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayListDrain<'_, T> {}
+
+impl<T> Drop for ArrayListDrain<'_, T> {
+    fn drop(&mut self) {
+        let ptr = self.list.ptr();
+        let rest = ptr::slice_from_raw_parts_mut(unsafe { ptr.add(self.front) }, self.back - self.front);
+        self.front = self.back;
+        // a panicking Drop in the rest leaks the tail, list.len() still only covers the front
+        unsafe {
+            ptr::drop_in_place(rest);
+            let start = self.list.len();
+            ptr::copy(ptr.add(self.tail), ptr.add(start), self.tail_len);
+            self.list.set_len(start + self.tail_len);
         }
     }
 }
 
-impl<'a, T: Default + Debug> IntoIterator for &'a ArrayList<T> {
+impl<'a, T> IntoIterator for &'a ArrayList<T> {
     type Item = &'a T;
     type IntoIter = ArrayListIterator<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        ArrayListIterator {
-            current: 0,
+        ArrayListIterator::new(self.as_slice())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArrayList<T> {
+    type Item = &'a mut T;
+    type IntoIter = ArrayListIterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ArrayListIterMut::new(self.as_mut_slice())
+    }
+}
+
+impl<T> IntoIterator for ArrayList<T> {
+    type Item = T;
+    type IntoIter = ArrayListIntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let back = self.length;
+        // the iterator owns the elements from here on
+        self.length = 0;
+        ArrayListIntoIter {
             list: self,
+            front: 0,
+            back,
         }
     }
 }
 
+impl<T> Deref for ArrayList<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for ArrayList<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+// list[i], list[a..b], list[..] ... anything a slice can be indexed with
+impl<T, I: SliceIndex<[T]>> Index<I> for ArrayList<T> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> IndexMut<I> for ArrayList<T> {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl<T> AsRef<[T]> for ArrayList<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsMut<[T]> for ArrayList<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+// takes over the vec's buffer, O(1), the capacity carries over
+impl<T> From<Vec<T>> for ArrayList<T> {
+    fn from(vec: Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let (ptr, length, capacity) = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+        // MaybeUninit<T> has the layout of T, and len == capacity keeps into_boxed_slice from reallocating
+        let inner = unsafe { Vec::from_raw_parts(ptr as *mut MaybeUninit<T>, capacity, capacity) };
+        ArrayList::from_parts(length, inner.into_boxed_slice(), GrowthPolicy::default())
+    }
+}
+
+impl<T> FromIterator<T> for ArrayList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut list = ArrayList::with_capacity(iter.size_hint().0);
+        for item in iter {
+            list.append(item);
+        }
+        list
+    }
+}
+
+impl<T> Extend<T> for ArrayList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.append(item);
+        }
+    }
+}
+
+impl<T: Clone> Clone for ArrayList<T> {
+    fn clone(&self) -> Self {
+        let mut list = ArrayList::with_growth_policy(self.length, self.growth.clone());
+        for item in self.iter() {
+            list.append(item.clone());
+        }
+        list
+    }
+}
+
+// equal when the live elements are, capacity doesn't matter
+impl<T: PartialEq> PartialEq for ArrayList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for ArrayList<T> {}
+
+// hashes like the slice of its elements
+impl<T: Hash> Hash for ArrayList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for ArrayList<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ArrayList")
             .field("length", &self.length)
-            .field("tail", &self.tail)
-            .field("inner", &&self.inner[..])
+            .field("capacity", &self.capacity())
+            .field("inner", &self.as_slice())
             .finish()
     }
 }
-impl<T: Default + Debug> Default for ArrayList<T> {
+impl<T> Default for ArrayList<T> {
     fn default() -> Self {
         Self::new()
     }
@@ -289,6 +768,7 @@ impl<T: Default + Debug> Default for ArrayList<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::containers::lists::test_helpers::DropCounter;
 
     #[test]
     fn array_list_set_test() {
@@ -309,34 +789,34 @@ mod tests {
     #[test]
     fn arr_list_append() {
         let mut arr = ArrayList::<i32>::new();
-        assert_eq!(arr.length, 0);
+        assert_eq!(arr.len(), 0);
         arr.append(42);
-        assert_eq!(arr.length, 1);
+        assert_eq!(arr.len(), 1);
     }
 
     #[test]
     fn arr_list_prepend() {
         let mut arr = ArrayList::<i32>::new();
-        assert_eq!(arr.length, 0);
+        assert_eq!(arr.len(), 0);
 
         // Prepend an element
         arr.prepend(42);
-        assert_eq!(arr.length, 1);
+        assert_eq!(arr.len(), 1);
         assert_eq!(arr.get(0), Some(&42));
         // Prepend another element
         arr.prepend(41);
-        assert_eq!(arr.length, 2);
+        assert_eq!(arr.len(), 2);
         assert_eq!(arr.get(0), Some(&41));
         assert_eq!(arr.get(1), Some(&42));
         // Prepend another element
         arr.prepend(40);
-        assert_eq!(arr.length, 3);
+        assert_eq!(arr.len(), 3);
         assert_eq!(arr.get(0), Some(&40));
         assert_eq!(arr.get(1), Some(&41));
         assert_eq!(arr.get(2), Some(&42));
         // Prepend another element
         arr.prepend(39);
-        assert_eq!(arr.length, 4);
+        assert_eq!(arr.len(), 4);
         assert_eq!(arr.get(0), Some(&39));
         assert_eq!(arr.get(1), Some(&40));
         assert_eq!(arr.get(2), Some(&41));
@@ -354,13 +834,13 @@ mod tests {
         }
 
         // Insert at index 0
-        arr.insert_at(0, 0);
+        arr.insert_at(0, 0).unwrap();
         assert_eq!(arr.len(), 11);
         assert_eq!(arr.get(0), Some(&0)); // First item should now be 0
         assert_eq!(arr.get(1), Some(&1)); // Second item should now be 1
 
         // Insert at index 5
-        arr.insert_at(5, 50);
+        arr.insert_at(5, 50).unwrap();
         assert_eq!(arr.len(), 12);
         assert_eq!(arr.get(5), Some(&50)); // The item at index 5 should be 50
         assert_eq!(arr.get(6), Some(&5)); // The item at index 6 should be 6 (the original item at index 5)
@@ -368,23 +848,28 @@ mod tests {
         // Insert at last index
 
         print!("arr: {:?}", arr);
-        arr.insert_at(11, 100);
+        arr.insert_at(11, 100).unwrap();
         print!("arr: {:?}", arr);
         assert_eq!(arr.len(), 13);
         assert_eq!(arr.get(11), Some(&100)); // The item at index 11 should be 100
         assert_eq!(arr.get(12), Some(&10)); // The item at index 12 should be 10
 
+        // Insert at the end appends
+        assert_eq!(arr.insert_at(13, 200), Ok(()));
+        assert_eq!(arr.len(), 14);
+        assert_eq!(arr.get(13), Some(&200));
+
         // Try to insert at an out-of-bounds index
-        arr.insert_at(100, 200);
-        assert_eq!(arr.len(), 13); // Length should not have changed
+        assert_eq!(arr.insert_at(100, 200), Err(ArrayListError::IndexOutOfBounds { index: 100, len: 14 }));
+        assert_eq!(arr.len(), 14); // Length should not have changed
     }
 
     #[test]
     fn arr_list_with_capacity() {
         let mut arr = ArrayList::<i32>::with_capacity(10);
-        assert_eq!(arr.length, 0);
+        assert_eq!(arr.len(), 0);
         arr.append(42);
-        assert_eq!(arr.length, 1);
+        assert_eq!(arr.len(), 1);
     }
 
     #[test]
@@ -421,10 +906,10 @@ mod tests {
 
         // Add assertions to test the behavior
         assert_eq!(arr.pop(), Some(44));
-        assert!(arr.length == 2);
+        assert_eq!(arr.len(), 2);
         assert_eq!(arr.pop(), Some(43));
         assert_eq!(arr.pop(), Some(42));
-        assert!(arr.length == 0);
+        assert!(arr.is_empty());
         assert_eq!(arr.pop(), None);
 
     }
@@ -539,7 +1024,7 @@ mod tests {
         assert_eq!(index, 11);
 
         // Insert an item at index 5
-        arr.insert_at(5, 50);
+        arr.insert_at(5, 50).unwrap();
 
         // Iterate over the array list again and check the items
         let expected_items = vec![1, 2, 3, 4, 5, 50, 6, 7, 8, 9, 10];
@@ -552,4 +1037,448 @@ mod tests {
         // Check that the iteration covered all items
         assert_eq!(index, expected_items.len());
     }
-}
\ No newline at end of file
+
+    // no Default, no Debug, no Clone
+    struct Opaque(u32);
+
+    #[test]
+    fn arr_list_without_default() {
+        let mut arr = ArrayList::new();
+        for i in 0..20 {
+            arr.append(Opaque(i));
+        }
+        arr.prepend(Opaque(100));
+        arr.insert_at(1, Opaque(200)).unwrap();
+        assert_eq!(arr.len(), 22);
+        assert_eq!(arr.get(0).map(|x| x.0), Some(100));
+        assert_eq!(arr.get(1).map(|x| x.0), Some(200));
+        assert_eq!(arr.pop().map(|x| x.0), Some(19));
+        assert_eq!(arr.pop_at(0).map(|x| x.0), Some(100));
+        assert_eq!(arr.get(20).map(|x| x.0), None);
+    }
+
+    #[test]
+    fn arr_list_drops_exactly_live_elements() {
+        let drops = std::cell::Cell::new(0);
+        let mut arr = ArrayList::with_capacity(2);
+
+        // grows several times, growing must not drop anything
+        for _ in 0..50 {
+            arr.append(DropCounter(&drops));
+        }
+        arr.prepend(DropCounter(&drops));
+        arr.insert_at(10, DropCounter(&drops)).unwrap();
+        assert_eq!(drops.get(), 0);
+
+        drop(arr.pop());
+        drop(arr.pop_at(0));
+        assert_eq!(drops.get(), 2);
+        arr.set(5, DropCounter(&drops));
+        assert_eq!(drops.get(), 3);
+
+        arr.clear();
+        assert_eq!(drops.get(), 53);
+        arr.append(DropCounter(&drops));
+        arr.append(DropCounter(&drops));
+        drop(arr);
+        assert_eq!(drops.get(), 55);
+    }
+
+    #[test]
+    fn arr_list_zero_sized_types() {
+        let mut arr = ArrayList::new();
+        assert_eq!(arr.capacity(), usize::MAX);
+        for _ in 0..1000 {
+            arr.append(());
+        }
+        arr.prepend(());
+        arr.insert_at(3, ()).unwrap();
+        assert_eq!(arr.len(), 1002);
+        assert_eq!(arr.pop(), Some(()));
+        assert_eq!(arr.pop_at(500), Some(()));
+        assert_eq!(arr.get(999), Some(&()));
+        assert_eq!(arr.get(1000), None);
+        assert_eq!(arr.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn arr_list_zero_capacity() {
+        let mut arr = ArrayList::<String>::with_capacity(0);
+        assert_eq!(arr.capacity(), 0);
+        assert_eq!(arr.pop(), None);
+        assert_eq!(arr.pop_at(0), None);
+        arr.clear();
+
+        arr.prepend("b".to_string());
+        arr.prepend("a".to_string());
+        arr.append("c".to_string());
+        assert!(arr.capacity() >= 3);
+        assert_eq!(arr.as_slice(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn arr_list_as_slice() {
+        let mut arr: ArrayList<i32> = vec![5, 3, 9, 1, 7].into();
+        crate::algorithms::sorting::heap_sort::heapsort(&mut arr);
+        assert_eq!(arr[..], [1, 3, 5, 7, 9]);
+
+        assert_eq!(arr[1], 3);
+        assert_eq!(arr[1..3], [3, 5]);
+        assert_eq!(arr[3..], [7, 9]);
+        arr[0] = 10;
+        arr[1..4].reverse();
+        assert_eq!(arr.as_ref(), [10, 7, 5, 3, 9]);
+        arr.as_mut()[4] = 0;
+        assert_eq!(arr.iter().sum::<i32>(), 25);
+        assert_eq!(arr.binary_search(&99), Err(5));
+        assert!(arr.contains(&7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn arr_list_index_out_of_bounds() {
+        // capacity is bigger, but only the live elements can be indexed
+        let mut arr = ArrayList::with_capacity(5);
+        arr.append(1);
+        let _ = arr[1];
+    }
+
+    #[test]
+    fn arr_list_from_and_collect() {
+        let vec: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        let capacity = vec.capacity();
+        let arr = ArrayList::from(vec.clone());
+        assert_eq!(arr.capacity(), capacity);
+        assert_eq!(arr[..], vec[..]);
+
+        let mut collected: ArrayList<String> = vec.iter().cloned().collect();
+        assert_eq!(collected, arr);
+        collected.extend(vec![String::from("10"), String::from("11")]);
+        assert_eq!(collected.len(), 12);
+        assert_eq!(collected[11], "11");
+        assert_ne!(collected, arr);
+
+        let empty: ArrayList<()> = Vec::new().into();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn arr_list_clone_eq_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |x: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            x(&mut hasher);
+            hasher.finish()
+        };
+
+        let mut arr = ArrayList::with_capacity(100);
+        arr.extend((0..10).map(|i| vec![i; i]));
+        let copy = arr.clone();
+        assert_eq!(copy, arr);
+        assert_eq!(hash(&|h| copy.hash(h)), hash(&|h| arr.hash(h)));
+        assert_eq!(hash(&|h| arr.hash(h)), hash(&|h| arr.as_slice().hash(h)));
+
+        arr[3].push(1);
+        assert_ne!(copy, arr);
+        assert_eq!(copy[3], vec![3, 3, 3]);
+    }
+
+    fn list_of(items: &[i32]) -> ArrayList<i32> {
+        ArrayList::from(items.to_vec())
+    }
+
+    #[test]
+    fn arr_list_insert_slice_and_extend() {
+        let mut arr = list_of(&[1, 2, 6]);
+        arr.insert_slice(2, &[3, 4, 5]).unwrap();
+        arr.insert_slice(0, &[0]).unwrap();
+        arr.insert_slice(7, &[7, 8]).unwrap();
+        arr.insert_slice(3, &[]).unwrap();
+        assert_eq!(arr[..], [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(arr.insert_slice(10, &[1]), Err(ArrayListError::IndexOutOfBounds { index: 10, len: 9 }));
+
+        arr.extend_from_slice(&[9, 10]);
+        assert_eq!(arr.len(), 11);
+        assert_eq!(arr[10], 10);
+    }
+
+    #[test]
+    fn arr_list_remove_range_and_drain() {
+        let mut arr = list_of(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        arr.remove_range(2..4).unwrap();
+        assert_eq!(arr[..], [0, 1, 4, 5, 6, 7, 8, 9]);
+        arr.remove_range(..=1).unwrap();
+        assert_eq!(arr[..], [4, 5, 6, 7, 8, 9]);
+
+        let drained: Vec<_> = arr.drain(1..4).unwrap().collect();
+        assert_eq!(drained, [5, 6, 7]);
+        assert_eq!(arr[..], [4, 8, 9]);
+
+        // a partly consumed drain still removes the whole range
+        let mut drain = arr.drain(..2).unwrap();
+        assert_eq!(drain.len(), 2);
+        assert_eq!(drain.next_back(), Some(8));
+        drop(drain);
+        assert_eq!(arr[..], [9]);
+
+        assert_eq!(arr.remove_range(1..3), Err(ArrayListError::InvalidRange { len: 1 }));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = arr.drain(1..0).err();
+        assert_eq!(reversed, Some(ArrayListError::InvalidRange { len: 1 }));
+        assert_eq!(arr.drain(1..).unwrap().count(), 0);
+        assert_eq!(arr[..], [9]);
+    }
+
+    #[test]
+    fn arr_list_splice() {
+        let mut arr = list_of(&[1, 2, 3, 4, 5]);
+        let removed = arr.splice(1..3, [20, 30, 40]).unwrap();
+        assert_eq!(removed[..], [2, 3]);
+        assert_eq!(arr[..], [1, 20, 30, 40, 4, 5]);
+
+        let removed = arr.splice(.., Vec::new()).unwrap();
+        assert_eq!(removed.len(), 6);
+        assert!(arr.is_empty());
+        arr.splice(0..0, 0..3).unwrap();
+        assert_eq!(arr[..], [0, 1, 2]);
+        assert!(arr.splice(2..5, [1]).is_err());
+    }
+
+    #[test]
+    fn arr_list_retain_and_dedup() {
+        let mut arr: ArrayList<i32> = (0..20).collect();
+        arr.retain(|x| x % 3 == 0);
+        assert_eq!(arr[..], [0, 3, 6, 9, 12, 15, 18]);
+        arr.retain(|_| false);
+        assert!(arr.is_empty());
+
+        let mut arr = list_of(&[1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        arr.dedup();
+        assert_eq!(arr[..], [1, 2, 3, 1, 4]);
+        arr.dedup_by(|a, b| *a - *b == 1);
+        assert_eq!(arr[..], [1, 3, 1, 4]);
+    }
+
+    #[test]
+    fn arr_list_truncate_split_off_swap_remove() {
+        let mut arr = list_of(&[0, 1, 2, 3, 4, 5]);
+        arr.truncate(10);
+        assert_eq!(arr.len(), 6);
+        arr.truncate(5);
+        assert_eq!(arr[..], [0, 1, 2, 3, 4]);
+
+        let tail = arr.split_off(3).unwrap();
+        assert_eq!(arr[..], [0, 1, 2]);
+        assert_eq!(tail[..], [3, 4]);
+        assert!(arr.split_off(3).unwrap().is_empty());
+        assert_eq!(arr.split_off(4).err(), Some(ArrayListError::IndexOutOfBounds { index: 4, len: 3 }));
+
+        assert_eq!(arr.swap_remove(0), Ok(0));
+        assert_eq!(arr[..], [2, 1]);
+        assert_eq!(arr.swap_remove(1), Ok(1));
+        assert_eq!(arr.swap_remove(1), Err(ArrayListError::IndexOutOfBounds { index: 1, len: 1 }));
+    }
+
+    #[test]
+    fn arr_list_range_ops_drop_exactly_once() {
+        let drops = std::cell::Cell::new(0);
+        let mut arr: ArrayList<_> = (0..20).map(|_| DropCounter(&drops)).collect();
+
+        arr.remove_range(0..3).unwrap();
+        assert_eq!(drops.get(), 3);
+        arr.drain(..5).unwrap().next();
+        assert_eq!(drops.get(), 8);
+        let mut i = 0;
+        arr.retain(|_| {
+            i += 1;
+            i % 2 == 0
+        });
+        assert_eq!(drops.get(), 14);
+        arr.dedup_by(|_, _| true);
+        assert_eq!(drops.get(), 19);
+        drop(arr.splice(0..1, (0..4).map(|_| DropCounter(&drops))).unwrap());
+        assert_eq!(drops.get(), 20);
+        drop(arr.split_off(2).unwrap());
+        assert_eq!(drops.get(), 22);
+        arr.truncate(1);
+        drop(arr.swap_remove(0));
+        assert_eq!(drops.get(), 24);
+        assert!(arr.is_empty());
+
+        // a leaked drain leaks, it doesn't double drop
+        let mut arr: ArrayList<_> = (0..4).map(|_| DropCounter(&drops)).collect();
+        std::mem::forget(arr.drain(1..2).unwrap());
+        assert_eq!(arr.len(), 1);
+        drop(arr);
+        assert_eq!(drops.get(), 25);
+    }
+
+    #[test]
+    fn arr_list_new_does_not_allocate() {
+        let mut arr = ArrayList::<u64>::new();
+        assert_eq!(arr.capacity(), 0);
+        assert_eq!(arr.memory_stats(), MemoryStats::default());
+        arr.append(1);
+        assert_eq!(arr.capacity(), MIN_CAPACITY);
+        assert_eq!(arr.memory_stats().grows, 1);
+        assert_eq!(arr.memory_stats().bytes, MIN_CAPACITY * 8);
+    }
+
+    #[test]
+    fn arr_list_growth_policies() {
+        let capacities = |growth: GrowthPolicy| {
+            let mut arr = ArrayList::with_growth_policy(0, growth);
+            let mut seen = vec![];
+            for i in 0..40 {
+                arr.append(i);
+                if seen.last() != Some(&arr.capacity()) {
+                    seen.push(arr.capacity());
+                }
+            }
+            assert_eq!(arr[..], (0..40).collect::<Vec<_>>()[..]);
+            seen
+        };
+
+        assert_eq!(capacities(GrowthPolicy::Doubling), [4, 8, 16, 32, 64]);
+        assert_eq!(capacities(GrowthPolicy::OneAndHalf), [4, 6, 9, 13, 19, 28, 42]);
+        assert_eq!(capacities(GrowthPolicy::FixedIncrement(15)), [15, 30, 45]);
+        assert_eq!(capacities(GrowthPolicy::FixedIncrement(0)), (1..=40).collect::<Vec<_>>());
+        assert_eq!(capacities(GrowthPolicy::Custom(Arc::new(|c| c * 3 + 1))), [1, 4, 13, 40]);
+        // a policy that doesn't grow still gets the room that is needed
+        assert_eq!(capacities(GrowthPolicy::Custom(Arc::new(|c| c))).len(), 40);
+
+        let mut arr = ArrayList::with_growth_policy(2, GrowthPolicy::FixedIncrement(3));
+        arr.extend(0..3);
+        assert_eq!(arr.capacity(), 5);
+        arr.set_growth_policy(GrowthPolicy::Doubling);
+        arr.extend(0..3);
+        assert_eq!(arr.capacity(), 10);
+        assert_eq!(arr.clone().growth_policy().next_capacity(10), 20);
+    }
+
+    #[test]
+    fn arr_list_reserve() {
+        let mut arr = ArrayList::<i32>::new();
+        arr.reserve(10);
+        assert_eq!(arr.capacity(), 10);
+        arr.reserve(10);
+        assert_eq!(arr.memory_stats().grows, 1);
+        arr.extend(0..10);
+        arr.reserve(1);
+        assert_eq!(arr.capacity(), 20);
+        arr.reserve_exact(15);
+        assert_eq!(arr.capacity(), 25);
+        arr.reserve_exact(5);
+        assert_eq!(arr.capacity(), 25);
+        assert_eq!(arr.memory_stats().grows, 3);
+        assert_eq!(arr.memory_stats().moved, 20);
+        assert_eq!(arr[..], (0..10).collect::<Vec<_>>()[..]);
+    }
+
+    #[test]
+    fn arr_list_shrink() {
+        let mut arr: ArrayList<String> = (0..100).map(|i| i.to_string()).collect();
+        arr.truncate(10);
+        let before = arr.memory_stats();
+        assert!(arr.capacity() >= 100);
+
+        arr.shrink_to(50);
+        assert_eq!(arr.capacity(), 50);
+        arr.shrink_to(60);
+        assert_eq!(arr.capacity(), 50);
+        arr.shrink_to_fit();
+        assert_eq!(arr.capacity(), 10);
+        assert_eq!(arr[9], "9");
+
+        let after = arr.memory_stats();
+        assert_eq!(after.shrinks, before.shrinks + 2);
+        assert_eq!(after.peak_capacity, before.peak_capacity);
+        assert_eq!(after.bytes, 10 * std::mem::size_of::<String>());
+        assert_eq!(after.peak_bytes, before.peak_bytes);
+
+        arr.clear();
+        arr.shrink_to_fit();
+        assert_eq!(arr.capacity(), 0);
+        arr.append("again".to_string());
+        assert_eq!(arr[0], "again");
+
+        // zero sized types never reallocate
+        let mut arr: ArrayList<()> = ArrayList::new();
+        arr.shrink_to_fit();
+        arr.reserve_exact(10);
+        assert_eq!(arr.capacity(), usize::MAX);
+        assert_eq!(arr.memory_stats().grows + arr.memory_stats().shrinks, 0);
+    }
+
+    #[test]
+    fn arr_list_iter_double_ended() {
+        let arr: ArrayList<i32> = (1..=5).collect();
+        let mut iter = arr.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), [&2, &3, &4]);
+
+        assert_eq!(arr.rev().copied().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+        let pairs: Vec<(i32, i32)> = arr.iter().zip(arr.rev()).map(|(a, b)| (*a, *b)).collect();
+        assert_eq!(pairs[0], (1, 5));
+        assert_eq!(ArrayList::<i32>::new().iter().next_back(), None);
+    }
+
+    #[test]
+    fn arr_list_iter_mut() {
+        let mut arr: ArrayList<i32> = (1..=5).collect();
+        for x in arr.iter_mut() {
+            *x *= 10;
+        }
+        for x in &mut arr {
+            *x += 1;
+        }
+        if let Some(last) = arr.iter_mut().next_back() {
+            *last = 0;
+        }
+        assert_eq!(arr.iter_mut().len(), 5);
+        assert_eq!(arr[..], [11, 21, 31, 41, 0]);
+    }
+
+    #[test]
+    fn arr_list_into_iter() {
+        let arr: ArrayList<String> = (0..6).map(|i| i.to_string()).collect();
+        let mut iter = arr.into_iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next().as_deref(), Some("0"));
+        assert_eq!(iter.next_back().as_deref(), Some("5"));
+        let rest: Vec<String> = iter.collect();
+        assert_eq!(rest, ["1", "2", "3", "4"]);
+
+        let arr: ArrayList<i32> = (0..4).collect();
+        let mut sum = 0;
+        for x in arr {
+            sum += x;
+        }
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn arr_list_into_iter_drops_the_rest() {
+        let drops = std::cell::Cell::new(0);
+        let arr: ArrayList<_> = (0..10).map(|_| DropCounter(&drops)).collect();
+        let mut iter = arr.into_iter();
+        drop(iter.next());
+        drop(iter.next_back());
+        assert_eq!(drops.get(), 2);
+        drop(iter);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn arr_list_windows_and_chunks() {
+        let arr: ArrayList<i32> = (1..=7).collect();
+        let sums: Vec<i32> = arr.windows(3).map(|w| w.iter().sum()).collect();
+        assert_eq!(sums, [6, 9, 12, 15, 18]);
+        let chunks: Vec<&[i32]> = arr.chunks(3).collect();
+        assert_eq!(chunks, [&[1, 2, 3][..], &[4, 5, 6], &[7]]);
+        assert_eq!(arr.windows(8).count(), 0);
+    }
+}
@@ -0,0 +1,517 @@
+/***
+ * ArrayVecList
+ * ArrayList with a fixed capacity of N elements, stored inline, never allocates
+ * append/pop/get has O(1)
+ * prepend has O(n)
+ *
+ * Same API as ArrayList and SmallArrayList, swapping one for another is a change of type.
+ * The one difference: whatever adds elements returns Err(CapacityExceeded) instead of
+ * growing, and leaves the list as it was. append, prepend, insert_at and splice take their
+ * items by value and hand them back in an ArrayVecListError, nothing is dropped on the way.
+ * insert_slice and extend_from_slice only borrow theirs. is_full / remaining_capacity tell
+ * up front whether it will fit. FromIterator and Extend have no way to report an error,
+ * they panic past N.
+ *
+ * Storage is an inline [MaybeUninit<T>; N], the element moving is shared with ArrayList.
+ *
+ * Upsides:
+ * - No heap allocation at all, works where allocating doesn't
+ * - The capacity is a hard bound, the list never grows past what was planned for
+ * - Elements live right next to the length, no pointer to chase
+ *
+ * Downsides:
+ * - N is fixed at compile time and all of it is paid for, even when empty
+ * - Moving the list moves all N slots
+ * - Every insert can fail and has to be handled
+ */
+
+use std::fmt;
+use std::iter::Chain;
+use std::mem::MaybeUninit;
+use std::ops::RangeBounds;
+use std::option;
+use std::ptr;
+use std::slice;
+use super::array_list::{ArrayListDrain, ArrayListError, ArrayListIterMut, ArrayListIterator};
+use super::raw_list::{check_insert_index, range_of, slice_list_impls, RawList};
+
+pub struct ArrayVecList<T, const N: usize> {
+    length: usize,
+    inner: [MaybeUninit<T>; N], // inner[..length] is initialized, the rest is not
+}
+
+// a failed append / prepend / insert_at / splice, why it failed and what wasn't inserted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayVecListError<T> {
+    pub error: ArrayListError,
+    pub rejected: T,
+}
+
+impl<T> ArrayVecListError<T> {
+    pub fn into_inner(self) -> T {
+        self.rejected
+    }
+}
+
+impl<T> fmt::Display for ArrayVecListError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ArrayVecListError<T> {}
+
+// the replacement a failed splice hands back: what it had read, then the unread rest
+pub type SpliceRejected<T, const N: usize, I> = Chain<Chain<ArrayVecListIntoIter<T, N>, option::IntoIter<T>>, I>;
+
+// owns the list, list.length is 0 so dropping the list drops nothing twice
+pub struct ArrayVecListIntoIter<T, const N: usize> {
+    list: ArrayVecList<T, N>,
+    front: usize, // inner[front..back] are the elements not yielded yet
+    back: usize,
+}
+
+impl<T, const N: usize> ArrayVecList<T, N> {
+    pub const fn new() -> Self {
+        ArrayVecList {
+            length: 0,
+            inner: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    // always N
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn remaining_capacity(&self) -> usize {
+        N - self.length
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.length == N
+    }
+
+    // Ok if additional more elements fit
+    fn check_room(&self, additional: usize) -> Result<(), ArrayListError> {
+        match additional <= self.remaining_capacity() {
+            true => Ok(()),
+            false => Err(ArrayListError::CapacityExceeded { capacity: N }),
+        }
+    }
+
+    pub fn append(&mut self, item: T) -> Result<(), ArrayVecListError<T>> {
+        let len = self.length;
+        self.insert_at(len, item)
+    }
+
+    pub fn prepend(&mut self, item: T) -> Result<(), ArrayVecListError<T>> {
+        self.insert_at(0, item)
+    }
+
+    // insert el at index, everything from index on moves one to the right. index == len appends
+    pub fn insert_at(&mut self, index: usize, el: T) -> Result<(), ArrayVecListError<T>> {
+        let checked = check_insert_index(index, self.length).and_then(|_| self.check_room(1));
+        if let Err(error) = checked {
+            return Err(ArrayVecListError { error, rejected: el });
+        }
+        self.raw().insert(index, el);
+        Ok(())
+    }
+
+    // clones items in front of index, index == len appends them. All of them fit or none is inserted
+    pub fn insert_slice(&mut self, index: usize, items: &[T]) -> Result<(), ArrayListError>
+    where
+        T: Clone,
+    {
+        check_insert_index(index, self.length)?;
+        self.check_room(items.len())?;
+        // cloned up front, a panicking clone leaves self untouched
+        let mut cloned = ArrayVecList::<T, N>::new();
+        for item in items {
+            cloned.raw().push(item.clone());
+        }
+        self.raw().insert_from(index, cloned.raw());
+        Ok(())
+    }
+
+    pub fn extend_from_slice(&mut self, items: &[T]) -> Result<(), ArrayListError>
+    where
+        T: Clone,
+    {
+        self.check_room(items.len())?;
+        for item in items {
+            self.raw().push(item.clone());
+        }
+        Ok(())
+    }
+
+    // drops the elements in range and closes the gap
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) -> Result<(), ArrayListError> {
+        self.drain(range)?;
+        Ok(())
+    }
+
+    // removes range and yields its elements, see ArrayList::drain
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Result<ArrayListDrain<'_, T>, ArrayListError> {
+        let (start, end) = range_of(range, self.length)?;
+        Ok(ArrayListDrain::new(self.raw(), start, end))
+    }
+
+    /*
+        Replaces range with replace_with and returns the removed elements. replace_with is
+        read before anything is removed, never further than one element past what fits. If
+        the result wouldn't fit the list is left as it was, and the error hands back the
+        replacement: the elements read so far followed by the unread rest of replace_with.
+    */
+    #[allow(clippy::type_complexity)]
+    pub fn splice<R, I>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> Result<ArrayVecList<T, N>, ArrayVecListError<SpliceRejected<T, N, I::IntoIter>>>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let mut rest = replace_with.into_iter();
+        let mut items = ArrayVecList::<T, N>::new();
+        let (start, end) = match range_of(range, self.length) {
+            Ok(range) => range,
+            Err(error) => {
+                let rejected = items.into_iter().chain(None).chain(rest);
+                return Err(ArrayVecListError { error, rejected });
+            }
+        };
+        // room <= N, so items never fills up before the check below
+        let room = self.remaining_capacity() + (end - start);
+        while items.len() < room {
+            match rest.next() {
+                Some(item) => items.raw().push(item),
+                None => break,
+            }
+        }
+        if items.len() == room {
+            if let Some(extra) = rest.next() {
+                let error = ArrayListError::CapacityExceeded { capacity: N };
+                let rejected = items.into_iter().chain(Some(extra)).chain(rest);
+                return Err(ArrayVecListError { error, rejected });
+            }
+        }
+        let mut removed = ArrayVecList::new();
+        for item in ArrayListDrain::new(self.raw(), start, end) {
+            removed.raw().push(item);
+        }
+        self.raw().insert_from(start, items.raw());
+        Ok(removed)
+    }
+
+    // keeps the elements keep returns true for, in order
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, keep: F) {
+        self.raw().retain(keep);
+    }
+
+    // same(a, b) is called with a later element and the last kept one, true drops a
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, same: F) {
+        self.raw().dedup_by(same);
+    }
+
+    // drops everything from len on, does nothing if the list is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        self.raw().truncate(len);
+    }
+
+    // moves the elements from at on into a new list, at == len returns an empty one
+    pub fn split_off(&mut self, at: usize) -> Result<ArrayVecList<T, N>, ArrayListError> {
+        check_insert_index(at, self.length)?;
+        let mut other = ArrayVecList::new();
+        self.raw().split_into(at, other.raw());
+        Ok(other)
+    }
+
+    // removes the element at index and moves the last one into its place, O(1), doesn't keep order
+    pub fn swap_remove(&mut self, index: usize) -> Result<T, ArrayListError> {
+        let len = self.length;
+        self.raw().swap_remove(index).ok_or(ArrayListError::IndexOutOfBounds { index, len })
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.raw().pop()
+    }
+
+    pub fn pop_at(&mut self, index: usize) -> Option<T> {
+        self.raw().remove(index)
+    }
+
+    // the live elements
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.inner.as_ptr() as *const T, self.length) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.inner.as_mut_ptr() as *mut T, self.length) }
+    }
+
+    pub(super) fn raw(&mut self) -> RawList<'_, T> {
+        unsafe { RawList::new(&mut self.inner, &mut self.length) }
+    }
+}
+
+slice_list_impls!(ArrayVecList);
+
+impl<T, const N: usize> Drop for ArrayVecList<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Iterator for ArrayVecListIntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        // every slot in front..back is read exactly once
+        let item = unsafe { self.list.inner[self.front].assume_init_read() };
+        self.front += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for ArrayVecListIntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(unsafe { self.list.inner[self.back].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for ArrayVecListIntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for ArrayVecListIntoIter<T, N> {
+    fn drop(&mut self) {
+        let ptr = self.list.inner.as_mut_ptr() as *mut T;
+        let rest = ptr::slice_from_raw_parts_mut(unsafe { ptr.add(self.front) }, self.back - self.front);
+        self.front = self.back;
+        unsafe { ptr::drop_in_place(rest) };
+    }
+}
+
+// the elements not yielded yet, so a rejected splice replacement can be printed
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVecListIntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ptr = self.list.inner.as_ptr() as *const T;
+        let rest = unsafe { slice::from_raw_parts(ptr.add(self.front), self.back - self.front) };
+        f.debug_tuple("ArrayVecListIntoIter").field(&rest).finish()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVecList<T, N> {
+    type Item = T;
+    type IntoIter = ArrayVecListIntoIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let back = self.length;
+        // the iterator owns the elements from here on
+        self.length = 0;
+        ArrayVecListIntoIter {
+            list: self,
+            front: 0,
+            back,
+        }
+    }
+}
+
+// panics if the iterator yields more than N elements
+impl<T, const N: usize> FromIterator<T> for ArrayVecList<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = ArrayVecList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// panics once the list is full
+impl<T, const N: usize> Extend<T> for ArrayVecList<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            if self.append(item).is_err() {
+                panic!("ArrayVecList capacity exceeded");
+            }
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVecList<T, N> {
+    fn clone(&self) -> Self {
+        let mut list = ArrayVecList::new();
+        for item in self.iter() {
+            list.raw().push(item.clone());
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVecList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayVecList")
+            .field("length", &self.length)
+            .field("capacity", &N)
+            .field("inner", &self.as_slice())
+            .finish()
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::lists::test_helpers::DropCounter;
+    use std::cell::Cell;
+
+    #[test]
+    fn append_until_full() {
+        let mut list = ArrayVecList::<i32, 3>::new();
+        assert_eq!(list.capacity(), 3);
+        assert_eq!(list.append(1), Ok(()));
+        assert_eq!(list.append(2), Ok(()));
+        assert_eq!(list.prepend(0), Ok(()));
+        assert!(list.is_full());
+
+        let full = ArrayListError::CapacityExceeded { capacity: 3 };
+        assert_eq!(list.append(3), Err(ArrayVecListError { error: full, rejected: 3 }));
+        assert_eq!(list.prepend(4), Err(ArrayVecListError { error: full, rejected: 4 }));
+        assert_eq!(list.insert_at(1, 5).unwrap_err().into_inner(), 5);
+        assert_eq!(list.as_slice(), &[0, 1, 2]);
+
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.remaining_capacity(), 1);
+        assert_eq!(list.insert_at(1, 5), Ok(()));
+        assert_eq!(list.as_slice(), &[0, 5, 1]);
+    }
+
+    #[test]
+    fn index_errors_come_first() {
+        let mut list = ArrayVecList::<i32, 2>::new();
+        list.extend([1, 2]);
+        // out of bounds wins over full
+        let out_of_bounds = ArrayListError::IndexOutOfBounds { index: 5, len: 2 };
+        assert_eq!(list.insert_at(5, 0), Err(ArrayVecListError { error: out_of_bounds, rejected: 0 }));
+        assert_eq!(list.swap_remove(2), Err(ArrayListError::IndexOutOfBounds { index: 2, len: 2 }));
+        assert_eq!(list.drain(1..3).err(), Some(ArrayListError::InvalidRange { len: 2 }));
+    }
+
+    #[test]
+    fn bulk_edits_fit_or_change_nothing() {
+        let mut list: ArrayVecList<i32, 6> = [1, 2, 3].into_iter().collect();
+        assert_eq!(list.extend_from_slice(&[4, 5, 6, 7]), Err(ArrayListError::CapacityExceeded { capacity: 6 }));
+        assert_eq!(list.insert_slice(1, &[9, 9, 9, 9]), Err(ArrayListError::CapacityExceeded { capacity: 6 }));
+        assert_eq!(list.as_slice(), &[1, 2, 3]);
+
+        list.insert_slice(1, &[8, 9]).unwrap();
+        assert_eq!(list.as_slice(), &[1, 8, 9, 2, 3]);
+
+        // removes 2 and adds 3, exactly filling the list
+        assert_eq!(list.splice(1..3, [0, 0, 0]).unwrap().len(), 2);
+        assert_eq!(list.as_slice(), &[1, 0, 0, 0, 2, 3]);
+        let rejected = list.splice(0..1, [7, 8, 9]).err().unwrap();
+        assert_eq!(rejected.error, ArrayListError::CapacityExceeded { capacity: 6 });
+        assert_eq!(rejected.into_inner().collect::<Vec<_>>(), vec![7, 8, 9]);
+        let rejected = list.splice(2..9, [7]).err().unwrap();
+        assert_eq!(rejected.error, ArrayListError::InvalidRange { len: 6 });
+        assert_eq!(rejected.into_inner().collect::<Vec<_>>(), vec![7]);
+        assert_eq!(list.as_slice(), &[1, 0, 0, 0, 2, 3]);
+
+        list.dedup();
+        assert_eq!(list.as_slice(), &[1, 0, 2, 3]);
+        let tail = list.split_off(2).unwrap();
+        assert_eq!((list.as_slice(), tail.as_slice()), (&[1, 0][..], &[2, 3][..]));
+        list.retain(|&x| x != 0);
+        assert_eq!(list.as_slice(), &[1]);
+    }
+
+    #[test]
+    fn drain_and_iterators() {
+        let mut list: ArrayVecList<i32, 8> = (0..8).collect();
+        let drained: Vec<i32> = list.drain(2..5).unwrap().rev().collect();
+        assert_eq!(drained, vec![4, 3, 2]);
+        assert_eq!(list.as_slice(), &[0, 1, 5, 6, 7]);
+
+        for x in &mut list {
+            *x *= 10;
+        }
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 10, 50, 60, 70]);
+        assert_eq!(list.rev().next(), Some(&70));
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter), "ArrayVecListIntoIter([10, 50, 60, 70])");
+        assert_eq!(iter.rev().collect::<Vec<_>>(), vec![70, 60, 50, 10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity exceeded")]
+    fn collect_past_capacity_panics() {
+        let _ = (0..5).collect::<ArrayVecList<i32, 4>>();
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let drops = Cell::new(0);
+        let mut list = ArrayVecList::<DropCounter, 4>::new();
+        for _ in 0..4 {
+            list.append(DropCounter(&drops)).unwrap();
+        }
+        // the rejected element comes back with the error
+        let rejected = list.append(DropCounter(&drops)).unwrap_err().into_inner();
+        assert_eq!(drops.get(), 0);
+        drop(rejected);
+        assert_eq!(drops.get(), 1);
+
+        // a splice that doesn't fit gives back the elements it read and the rest
+        let replacement = (0..3).map(|_| DropCounter(&drops));
+        let rejected = list.splice(..1, replacement).err().unwrap().into_inner();
+        assert_eq!(drops.get(), 1);
+        assert_eq!(rejected.count(), 3);
+        assert_eq!(drops.get(), 4);
+
+        list.truncate(3);
+        assert_eq!(drops.get(), 5);
+        let mut iter = list.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn zero_sized_and_zero_capacity() {
+        let mut units = ArrayVecList::<(), 3>::new();
+        while units.append(()).is_ok() {}
+        assert_eq!(units.len(), 3);
+
+        let mut none = ArrayVecList::<i32, 0>::new();
+        assert!(none.is_full());
+        assert_eq!(none.append(1).unwrap_err().into_inner(), 1);
+        assert_eq!(none.pop(), None);
+    }
+
+    #[test]
+    fn works_as_a_slice() {
+        let mut list: ArrayVecList<i32, 5> = [3, 1, 2].into_iter().collect();
+        list.sort();
+        assert_eq!(&list[..], &[1, 2, 3]);
+        assert_eq!(list.binary_search(&2), Ok(1));
+        assert_eq!(list, list.clone());
+        assert_eq!(format!("{:?}", list), "ArrayVecList { length: 3, capacity: 5, inner: [1, 2, 3] }");
+    }
+}
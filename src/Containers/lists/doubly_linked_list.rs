@@ -16,11 +16,18 @@
  * - Nodes allocated on heap are not cache friendly
  * - An additional pointer per node is required
  * - Implementation is more complex
+ *
+ * Sorting relinks nodes instead of moving data:
+ * - sort: stable bottom-up merge sort on the next links, prev links are restored in one
+ *   pass afterwards. O(n log n) time, O(1) extra space
+ * - insertion_sort: stable, walks back from the sorted tail, O(n + inversions)
  */
 
 use std::cell::{RefCell, Ref};
+use std::cmp::Ordering;
 use std::rc::{Rc, Weak};
 use std::fmt;
+use crate::algorithms::sorting::partial_order;
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>; // Strong reference to a node
 type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>; // Weak reference to a node
@@ -172,6 +179,197 @@ impl<T> DoublyLinkedList<T> {
         }
     }
 
+    pub fn sort(&mut self)
+    where
+        T: PartialOrd,
+    {
+        self.sort_by(partial_order);
+    }
+
+    // bottom-up: merges neighbouring runs of width 1, 2, 4 ... until one run is left
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        loop {
+            let mut rest = self.head.take();
+            let mut sorted = Chain::new();
+            let mut merges = 0;
+            while rest.is_some() {
+                let right = split_after(&rest, width);
+                let next = split_after(&right, width);
+                sorted.append(merge(rest, right, &mut compare));
+                rest = next;
+                merges += 1;
+            }
+
+            self.head = sorted.head;
+            if merges <= 1 {
+                break;
+            }
+            width *= 2;
+        }
+        self.relink_prev();
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&T) -> K,
+        K: PartialOrd,
+    {
+        self.sort_by(|a, b| partial_order(&key(a), &key(b)));
+    }
+
+    pub fn insertion_sort(&mut self)
+    where
+        T: PartialOrd,
+    {
+        self.insertion_sort_by(partial_order);
+    }
+
+    // moves every node back past the bigger ones before it, like insertion sort on a slice
+    pub fn insertion_sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut rest = self.head.take();
+        let mut tail: Link<T> = None;
+
+        while let Some(node) = rest {
+            rest = node.borrow_mut().next.take();
+
+            // node goes between before and after, stops at the first node that isn't bigger
+            let mut before = tail.clone();
+            let mut after: Link<T> = None;
+            while let Some(b) = before.clone() {
+                if !is_less(&node, &b, &mut compare) {
+                    break;
+                }
+                before = b.borrow().prev.as_ref().and_then(Weak::upgrade);
+                after = Some(b);
+            }
+
+            match &after {
+                Some(a) => {
+                    a.borrow_mut().prev = Some(Rc::downgrade(&node));
+                    node.borrow_mut().next = Some(Rc::clone(a));
+                }
+                None => tail = Some(Rc::clone(&node)),
+            }
+            match &before {
+                Some(b) => {
+                    node.borrow_mut().prev = Some(Rc::downgrade(b));
+                    b.borrow_mut().next = Some(node);
+                }
+                None => {
+                    node.borrow_mut().prev = None;
+                    self.head = Some(node);
+                }
+            }
+        }
+
+        self.tail = tail.as_ref().map(Rc::downgrade);
+    }
+
+    pub fn insertion_sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&T) -> K,
+        K: PartialOrd,
+    {
+        self.insertion_sort_by(|a, b| partial_order(&key(a), &key(b)));
+    }
+
+    // points every prev link (and the tail) back at the right node after the next links changed
+    fn relink_prev(&mut self) {
+        let mut prev: WeakLink<T> = None;
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            node.borrow_mut().prev = prev;
+            prev = Some(Rc::downgrade(&node));
+            current = node.borrow().next.clone();
+        }
+        self.tail = prev;
+    }
+}
+
+// Sorting helpers, they only follow and move the next links
+
+// head and tail of a chain of nodes being built
+struct Chain<T> {
+    head: Link<T>,
+    tail: Link<T>,
+}
+
+impl<T> Chain<T> {
+    fn new() -> Self {
+        Chain { head: None, tail: None }
+    }
+
+    fn push(&mut self, node: Rc<RefCell<Node<T>>>) {
+        match self.tail.take() {
+            Some(tail) => tail.borrow_mut().next = Some(Rc::clone(&node)),
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        self.tail = Some(node);
+    }
+
+    fn append(&mut self, other: Chain<T>) {
+        if let Some(head) = other.head {
+            match self.tail.take() {
+                Some(tail) => tail.borrow_mut().next = Some(head),
+                None => self.head = Some(head),
+            }
+            self.tail = other.tail;
+        }
+    }
+}
+
+// cuts the chain after its first n nodes and returns the rest, None if it is that short
+fn split_after<T>(head: &Link<T>, n: usize) -> Link<T> {
+    let mut current = head.clone()?;
+    for _ in 1..n {
+        let next = current.borrow().next.clone()?;
+        current = next;
+    }
+    let rest = current.borrow_mut().next.take();
+    rest
+}
+
+// stable, takes from right only when it is strictly smaller
+fn merge<T, F>(mut left: Link<T>, mut right: Link<T>, compare: &mut F) -> Chain<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut merged = Chain::new();
+    while let (Some(l), Some(r)) = (&left, &right) {
+        let from = if is_less(r, l, compare) { &mut right } else { &mut left };
+        let node = from.take().unwrap();
+        *from = node.borrow_mut().next.take();
+        merged.push(node);
+    }
+
+    // the leftover side is already linked, only its tail has to be found
+    if let Some(rest) = left.or(right) {
+        let mut tail = Rc::clone(&rest);
+        merged.push(rest);
+        loop {
+            let next = tail.borrow().next.clone();
+            match next {
+                Some(next) => tail = next,
+                None => break,
+            }
+        }
+        merged.tail = Some(tail);
+    }
+    merged
+}
+
+fn is_less<T, F>(a: &Rc<RefCell<Node<T>>>, b: &Rc<RefCell<Node<T>>>, compare: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    compare(&a.borrow().data, &b.borrow().data) == Ordering::Less
 }
 
 pub struct DoublyLinkedListIterator<T> {
@@ -317,5 +515,95 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    fn list_of<T>(items: impl IntoIterator<Item = T>) -> DoublyLinkedList<T> {
+        let mut list = DoublyLinkedList::new_empty();
+        for item in items {
+            list.append(item);
+        }
+        list
+    }
+
+    // drains the list from the back, so the prev links and the tail get checked too
+    fn drain_back<T>(list: &mut DoublyLinkedList<T>) -> Vec<T> {
+        let mut items = Vec::new();
+        while let Some(item) = list.pop_back() {
+            items.push(item);
+        }
+        items.reverse();
+        items
+    }
+
+    #[test]
+    fn dll_sort() {
+        let mut list = list_of([5, 4, 3, 2, 1]);
+        list.sort();
+        assert_eq!(*list.peek_front().unwrap(), 1);
+        assert_eq!(drain_back(&mut list), vec![1, 2, 3, 4, 5]);
+
+        let mut empty: DoublyLinkedList<i32> = DoublyLinkedList::new_empty();
+        empty.sort();
+        empty.insertion_sort();
+        assert!(empty.pop_back().is_none());
+    }
+
+    #[test]
+    fn dll_sort_300() {
+        use rand::seq::SliceRandom;
+
+        for len in [1, 2, 3, 7, 255, 300] {
+            let mut items: Vec<i32> = (0..len).collect();
+            items.shuffle(&mut rand::thread_rng());
+
+            let mut merged = list_of(items.clone());
+            merged.sort();
+            let mut inserted = list_of(items);
+            inserted.insertion_sort();
+
+            // both ends still work after relinking
+            merged.prepend(-1);
+            merged.append(len);
+            inserted.prepend(-1);
+            inserted.append(len);
+            assert_eq!(drain_back(&mut merged), (-1..=len).collect::<Vec<_>>());
+            assert_eq!(drain_back(&mut inserted), (-1..=len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn dll_sort_stable() {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let items: Vec<(u8, u32)> = (0..500).map(|i| (rng.gen_range(0..10), i)).collect();
+        let mut expected = items.clone();
+        expected.sort_by_key(|p| p.0);
+
+        let mut merged = list_of(items.clone());
+        merged.sort_by_key(|p| p.0);
+        assert_eq!(drain_back(&mut merged), expected);
+
+        let mut inserted = list_of(items);
+        inserted.insertion_sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(drain_back(&mut inserted), expected);
+    }
+
+    #[test]
+    fn dll_insertion_sort_nearly_sorted() {
+        let mut items: Vec<i32> = (1..1000).collect();
+        items.swap(10, 11);
+        items.swap(500, 505);
+        items.push(0);
+
+        let mut list = list_of(items);
+        list.insertion_sort();
+        assert_eq!(*list.peek_front().unwrap(), 0);
+        assert_eq!(drain_back(&mut list), (0..1000).collect::<Vec<_>>());
+
+        // no Default needed, the list only moves nodes
+        let mut list = list_of(vec![vec![3], vec![1, 1], vec![2, 2, 2]]);
+        list.insertion_sort_by_key(|v| v[0]);
+        assert_eq!(drain_back(&mut list), vec![vec![1, 1], vec![2, 2, 2], vec![3]]);
+    }
+
     
 }
\ No newline at end of file
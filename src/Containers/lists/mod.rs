@@ -1,5 +1,11 @@
 // in root/src/container/lists/mod.rs
 
+pub mod array_deque;
 pub mod array_list;
+pub mod array_vec_list;
+pub mod small_array_list;
 pub mod singly_linked_list;
 pub mod doubly_linked_list;
+mod raw_list;
+#[cfg(test)]
+mod test_helpers;
@@ -0,0 +1,351 @@
+/***
+ * RawList
+ * The element moving shared by ArrayList, SmallArrayList and ArrayVecList
+ *
+ * A RawList borrows a buffer of MaybeUninit<T> together with the length that says how much
+ * of it is live, wherever the two are stored (a boxed slice, an inline array). Everything
+ * that moves, removes or drops elements without allocating is written once here.
+ * Nothing in here allocates: the lists make room before they insert, ArrayList and
+ * SmallArrayList by growing, ArrayVecList by returning CapacityExceeded when it can't.
+ * Inserting without room panics instead of writing past the buffer.
+ */
+
+use std::mem::MaybeUninit;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+use super::array_list::ArrayListError;
+
+pub(super) struct RawList<'a, T> {
+    buf: &'a mut [MaybeUninit<T>], // buf[..*len] is initialized, the rest is not
+    len: &'a mut usize,
+}
+
+impl<'a, T> RawList<'a, T> {
+    // buf[..*len] has to be initialized
+    pub(super) unsafe fn new(buf: &'a mut [MaybeUninit<T>], len: &'a mut usize) -> RawList<'a, T> {
+        debug_assert!(*len <= buf.len());
+        RawList { buf, len }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        *self.len
+    }
+
+    // free slots after the live elements
+    pub(super) fn room(&self) -> usize {
+        self.buf.len() - *self.len
+    }
+
+    pub(super) fn ptr(&mut self) -> *mut T {
+        self.buf.as_mut_ptr() as *mut T
+    }
+
+    // buf[..len] has to be initialized, and nothing past it is dropped from here on
+    pub(super) unsafe fn set_len(&mut self, len: usize) {
+        *self.len = len;
+    }
+
+    pub(super) fn push(&mut self, item: T) {
+        // indexing panics if there is no room
+        self.buf[*self.len].write(item);
+        *self.len += 1;
+    }
+
+    // index <= len, everything from index on moves one to the right
+    pub(super) fn insert(&mut self, index: usize, item: T) {
+        self.shift_right(index, 1);
+        self.buf[index].write(item);
+        *self.len += 1;
+    }
+
+    // moves all of items in front of index, items ends up empty
+    pub(super) fn insert_from(&mut self, index: usize, mut items: RawList<'_, T>) {
+        let count = items.len();
+        self.shift_right(index, count);
+        unsafe {
+            ptr::copy_nonoverlapping(items.ptr(), self.ptr().add(index), count);
+            items.set_len(0);
+        }
+        *self.len += count;
+    }
+
+    // moves buf[index..len] count slots to the right, needs count free slots
+    fn shift_right(&mut self, index: usize, count: usize) {
+        assert!(index <= *self.len && count <= self.room(), "no room to insert");
+        unsafe {
+            let ptr = self.ptr().add(index);
+            ptr::copy(ptr, ptr.add(count), *self.len - index);
+        }
+    }
+
+    pub(super) fn pop(&mut self) -> Option<T> {
+        if *self.len == 0 {
+            return None;
+        }
+        *self.len -= 1;
+        // the slot is past len now, so it's read exactly once
+        Some(unsafe { self.buf[*self.len].assume_init_read() })
+    }
+
+    // None if index is out of bounds
+    pub(super) fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= *self.len {
+            return None;
+        }
+        let item = unsafe { self.buf[index].assume_init_read() };
+        // close the gap, the last slot ends up as a stale duplicate past len
+        unsafe {
+            let ptr = self.ptr().add(index);
+            ptr::copy(ptr.add(1), ptr, *self.len - index - 1);
+        }
+        *self.len -= 1;
+        Some(item)
+    }
+
+    // removes the element at index and moves the last one into its place
+    pub(super) fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index >= *self.len {
+            return None;
+        }
+        *self.len -= 1;
+        unsafe {
+            let item = self.buf[index].assume_init_read();
+            let ptr = self.ptr();
+            ptr::copy(ptr.add(*self.len), ptr.add(index), 1);
+            Some(item)
+        }
+    }
+
+    pub(super) fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let len = *self.len;
+        // if keep panics the unvisited elements are leaked, never dropped twice
+        *self.len = 0;
+        let ptr = self.ptr();
+        let mut kept = 0;
+        for i in 0..len {
+            unsafe {
+                if keep(&*ptr.add(i)) {
+                    ptr::copy(ptr.add(i), ptr.add(kept), 1);
+                    kept += 1;
+                    *self.len = kept;
+                } else {
+                    ptr::drop_in_place(ptr.add(i));
+                }
+            }
+        }
+    }
+
+    pub(super) fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same: F) {
+        if *self.len < 2 {
+            return;
+        }
+        let len = *self.len;
+        // same leak on panic as retain
+        *self.len = 1;
+        let ptr = self.ptr();
+        let mut kept = 1;
+        for i in 1..len {
+            unsafe {
+                if same(&mut *ptr.add(i), &mut *ptr.add(kept - 1)) {
+                    ptr::drop_in_place(ptr.add(i));
+                } else {
+                    ptr::copy(ptr.add(i), ptr.add(kept), 1);
+                    kept += 1;
+                    *self.len = kept;
+                }
+            }
+        }
+    }
+
+    pub(super) fn truncate(&mut self, len: usize) {
+        if len >= *self.len {
+            return;
+        }
+        let tail = ptr::slice_from_raw_parts_mut(unsafe { self.ptr().add(len) }, *self.len - len);
+        // len first, a panicking Drop must not lead to a second drop of the same element
+        *self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    // moves buf[at..len] to the end of other, needs that much room in other
+    pub(super) fn split_into(&mut self, at: usize, mut other: RawList<'_, T>) {
+        assert!(at <= *self.len);
+        let count = *self.len - at;
+        assert!(count <= other.room(), "no room to split into");
+        unsafe {
+            ptr::copy_nonoverlapping(self.ptr().add(at), other.ptr().add(other.len()), count);
+        }
+        *self.len = at;
+        *other.len += count;
+    }
+}
+
+// insert positions go up to and including len
+pub(super) fn check_insert_index(index: usize, len: usize) -> Result<(), ArrayListError> {
+    match index <= len {
+        true => Ok(()),
+        false => Err(ArrayListError::IndexOutOfBounds { index, len }),
+    }
+}
+
+// start and end of range within len live elements
+pub(super) fn range_of<R: RangeBounds<usize>>(range: R, len: usize) -> Result<(usize, usize), ArrayListError> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => start.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => Some(len),
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end && end <= len => Ok((start, end)),
+        _ => Err(ArrayListError::InvalidRange { len }),
+    }
+}
+
+/*
+    The parts of the API that only need as_slice / as_mut_slice, implemented the same way for
+    SmallArrayList and ArrayVecList: the slice conversions, comparisons, borrowing iterators
+    and the read and replace methods ArrayList has.
+*/
+macro_rules! slice_list_impls {
+    ($list:ident) => {
+        impl<T, const N: usize> $list<T, N> {
+            pub fn len(&self) -> usize {
+                self.as_slice().len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            pub fn get(&self, index: usize) -> Option<&T> {
+                self.as_slice().get(index)
+            }
+
+            pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+                self.as_mut_slice().get_mut(index)
+            }
+
+            pub fn set(&mut self, index: usize, el: T) -> Option<T> {
+                let slot = self.get_mut(index)?;
+                Some(std::mem::replace(slot, el))
+            }
+
+            // removes consecutive repeated elements, the first of every run stays
+            pub fn dedup(&mut self)
+            where
+                T: PartialEq,
+            {
+                self.dedup_by(|a, b| a == b);
+            }
+
+            pub fn clear(&mut self) {
+                self.truncate(0);
+            }
+
+            pub fn iter(&self) -> ArrayListIterator<'_, T> {
+                ArrayListIterator::new(self.as_slice())
+            }
+
+            pub fn iter_mut(&mut self) -> ArrayListIterMut<'_, T> {
+                ArrayListIterMut::new(self.as_mut_slice())
+            }
+
+            pub fn rev(&self) -> std::iter::Rev<ArrayListIterator<'_, T>> {
+                self.iter().rev()
+            }
+
+            pub fn windows(&self, size: usize) -> std::slice::Windows<'_, T> {
+                self.as_slice().windows(size)
+            }
+
+            pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, T> {
+                self.as_slice().chunks(size)
+            }
+        }
+
+        impl<T, const N: usize> std::ops::Deref for $list<T, N> {
+            type Target = [T];
+
+            fn deref(&self) -> &[T] {
+                self.as_slice()
+            }
+        }
+
+        impl<T, const N: usize> std::ops::DerefMut for $list<T, N> {
+            fn deref_mut(&mut self) -> &mut [T] {
+                self.as_mut_slice()
+            }
+        }
+
+        impl<T, I: std::slice::SliceIndex<[T]>, const N: usize> std::ops::Index<I> for $list<T, N> {
+            type Output = I::Output;
+
+            fn index(&self, index: I) -> &I::Output {
+                &self.as_slice()[index]
+            }
+        }
+
+        impl<T, I: std::slice::SliceIndex<[T]>, const N: usize> std::ops::IndexMut<I> for $list<T, N> {
+            fn index_mut(&mut self, index: I) -> &mut I::Output {
+                &mut self.as_mut_slice()[index]
+            }
+        }
+
+        impl<T, const N: usize> AsRef<[T]> for $list<T, N> {
+            fn as_ref(&self) -> &[T] {
+                self.as_slice()
+            }
+        }
+
+        impl<T, const N: usize> AsMut<[T]> for $list<T, N> {
+            fn as_mut(&mut self) -> &mut [T] {
+                self.as_mut_slice()
+            }
+        }
+
+        impl<T: PartialEq, const N: usize> PartialEq for $list<T, N> {
+            fn eq(&self, other: &Self) -> bool {
+                self.as_slice() == other.as_slice()
+            }
+        }
+
+        impl<T: Eq, const N: usize> Eq for $list<T, N> {}
+
+        impl<T: std::hash::Hash, const N: usize> std::hash::Hash for $list<T, N> {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.as_slice().hash(state);
+            }
+        }
+
+        impl<T, const N: usize> Default for $list<T, N> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<'a, T, const N: usize> IntoIterator for &'a $list<T, N> {
+            type Item = &'a T;
+            type IntoIter = ArrayListIterator<'a, T>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a, T, const N: usize> IntoIterator for &'a mut $list<T, N> {
+            type Item = &'a mut T;
+            type IntoIter = ArrayListIterMut<'a, T>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+    };
+}
+
+pub(super) use slice_list_impls;
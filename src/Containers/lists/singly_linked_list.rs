@@ -16,11 +16,18 @@
  * - Indexing is inefficient, since it must iterate over all nodes
  * - Iteration in general involves a lot of jumping around in heap which takes time and is not cache friendly
  * - Reversing list is very inefficient
+ *
+ * Sorting relinks nodes instead of moving data, T is never cloned:
+ * - sort: stable bottom-up merge sort, O(n log n) time, O(1) extra space
+ * - insertion_sort: stable, the walk for a node starts at the previous insertion point when it can,
+ *   close to O(n) when nodes land near the one before them (nearly sorted), O(n^2) worst case
 
 */
 
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use crate::algorithms::sorting::partial_order;
 
 type SingleLink<T> = Option<Rc<RefCell<Node<T>>>>;
 
@@ -126,7 +133,111 @@ impl<T: Clone> SinglyLinkedList<T> {
         *self = Self::new_empty();
     }
 
+    pub fn sort(&mut self)
+    where
+        T: PartialOrd,
+    {
+        self.sort_by(partial_order);
+    }
+
+    // bottom-up: merges neighbouring runs of width 1, 2, 4 ... until one run is left
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        loop {
+            let mut rest = self.head.take();
+            let mut sorted = Chain::new();
+            let mut merges = 0;
+            while rest.is_some() {
+                let right = split_after(&rest, width);
+                let next = split_after(&right, width);
+                sorted.append(merge(rest, right, &mut compare));
+                rest = next;
+                merges += 1;
+            }
+
+            self.head = sorted.head;
+            self.tail = sorted.tail;
+            if merges <= 1 {
+                return;
+            }
+            width *= 2;
+        }
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&T) -> K,
+        K: PartialOrd,
+    {
+        self.sort_by(|a, b| partial_order(&key(a), &key(b)));
+    }
 
+    pub fn insertion_sort(&mut self)
+    where
+        T: PartialOrd,
+    {
+        self.insertion_sort_by(partial_order);
+    }
+
+    // builds the sorted list node by node, a node that isn't smaller than the tail is appended right away
+    pub fn insertion_sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut rest = self.head.take();
+        let mut sorted = Chain::new();
+        // the node the previous one went in after, a walk that may start there skips the prefix before it
+        let mut cursor: SingleLink<T> = None;
+
+        while let Some(node) = rest {
+            rest = node.borrow_mut().next.take();
+            match (sorted.head.clone(), sorted.tail.clone()) {
+                (Some(head), Some(tail)) if is_less(&node, &tail, &mut compare) => {
+                    let start = match cursor.take() {
+                        Some(at) if !is_less(&node, &at, &mut compare) => at,
+                        _ if is_less(&node, &head, &mut compare) => {
+                            node.borrow_mut().next = Some(head);
+                            sorted.head = Some(node.clone());
+                            cursor = Some(node);
+                            continue;
+                        }
+                        _ => head,
+                    };
+                    // insert after the last node that isn't bigger, keeps equal elements in order
+                    let mut at = start;
+                    loop {
+                        // node is smaller than the tail, the walk stops before running off the end
+                        let next = at.borrow().next.clone().unwrap();
+                        if is_less(&node, &next, &mut compare) {
+                            break;
+                        }
+                        at = next;
+                    }
+                    node.borrow_mut().next = at.borrow_mut().next.take();
+                    at.borrow_mut().next = Some(node);
+                    cursor = Some(at);
+                }
+                (_, tail) => {
+                    cursor = tail;
+                    sorted.push(node);
+                }
+            }
+        }
+
+        self.head = sorted.head;
+        self.tail = sorted.tail;
+    }
+
+    pub fn insertion_sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&T) -> K,
+        K: PartialOrd,
+    {
+        self.insertion_sort_by(|a, b| partial_order(&key(a), &key(b)));
+    }
 }
 
 impl<T: Clone> Node<T> {
@@ -138,6 +249,85 @@ impl<T: Clone> Node<T> {
     }
 }
 
+// Sorting helpers, they only ever move Rc's around
+
+// head and tail of a chain of nodes being built
+struct Chain<T: Clone> {
+    head: SingleLink<T>,
+    tail: SingleLink<T>,
+}
+
+impl<T: Clone> Chain<T> {
+    fn new() -> Self {
+        Chain { head: None, tail: None }
+    }
+
+    fn push(&mut self, node: Rc<RefCell<Node<T>>>) {
+        match self.tail.take() {
+            Some(tail) => tail.borrow_mut().next = Some(node.clone()),
+            None => self.head = Some(node.clone()),
+        }
+        self.tail = Some(node);
+    }
+
+    fn append(&mut self, other: Chain<T>) {
+        if let Some(head) = other.head {
+            match self.tail.take() {
+                Some(tail) => tail.borrow_mut().next = Some(head),
+                None => self.head = Some(head),
+            }
+            self.tail = other.tail;
+        }
+    }
+}
+
+// cuts the chain after its first n nodes and returns the rest, None if it is that short
+fn split_after<T: Clone>(head: &SingleLink<T>, n: usize) -> SingleLink<T> {
+    let mut current = head.clone()?;
+    for _ in 1..n {
+        let next = current.borrow().next.clone()?;
+        current = next;
+    }
+    let rest = current.borrow_mut().next.take();
+    rest
+}
+
+// stable, takes from right only when it is strictly smaller
+fn merge<T: Clone, F>(mut left: SingleLink<T>, mut right: SingleLink<T>, compare: &mut F) -> Chain<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut merged = Chain::new();
+    while let (Some(l), Some(r)) = (&left, &right) {
+        let from = if is_less(r, l, compare) { &mut right } else { &mut left };
+        let node = from.take().unwrap();
+        *from = node.borrow_mut().next.take();
+        merged.push(node);
+    }
+
+    // the leftover side is already linked, only its tail has to be found
+    if let Some(rest) = left.or(right) {
+        let mut tail = rest.clone();
+        merged.push(rest);
+        loop {
+            let next = tail.borrow().next.clone();
+            match next {
+                Some(next) => tail = next,
+                None => break,
+            }
+        }
+        merged.tail = Some(tail);
+    }
+    merged
+}
+
+fn is_less<T: Clone, F>(a: &Rc<RefCell<Node<T>>>, b: &Rc<RefCell<Node<T>>>, compare: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    compare(&a.borrow().data, &b.borrow().data) == Ordering::Less
+}
+
 // Trait implementations
 
 //Iterator, we need to define the associated type Item and the next() method
@@ -357,5 +547,101 @@ mod tests {
 
     }
 
+    fn list_of<T: Clone>(items: impl IntoIterator<Item = T>) -> SinglyLinkedList<T> {
+        let mut list = SinglyLinkedList::new_empty();
+        for item in items {
+            list.append(item);
+        }
+        list
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut list = list_of([5, 4, 3, 2, 1]);
+        list.sort();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+
+        let mut empty: SinglyLinkedList<i32> = SinglyLinkedList::new_empty();
+        empty.sort();
+        empty.insertion_sort();
+        assert!(empty.is_empty());
+
+        let mut single = list_of([42]);
+        single.sort();
+        assert_eq!(single.peek(), Some(42));
+        assert_eq!(single.peek_tail(), Some(42));
+    }
+
+    #[test]
+    fn sort_300_test() {
+        use rand::seq::SliceRandom;
+
+        // odd lengths leave a lonely run at the end of some passes
+        for len in [2, 3, 7, 255, 300] {
+            let mut items: Vec<i32> = (0..len).collect();
+            items.shuffle(&mut rand::thread_rng());
+
+            let mut merged = list_of(items.clone());
+            merged.sort();
+            let mut inserted = list_of(items);
+            inserted.insertion_sort();
+
+            // tail is relinked too, appending still goes to the end
+            assert_eq!(merged.peek_tail(), Some(len - 1));
+            merged.append(len);
+            inserted.append(len);
+            assert_eq!(merged.len, len as u64 + 1);
+            assert_eq!(merged.into_iter().collect::<Vec<_>>(), (0..=len).collect::<Vec<_>>());
+            assert_eq!(inserted.into_iter().collect::<Vec<_>>(), (0..=len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn sort_stable_test() {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let items: Vec<(u8, u32)> = (0..500).map(|i| (rng.gen_range(0..10), i)).collect();
+        let mut expected = items.clone();
+        expected.sort_by_key(|p| p.0);
+
+        let mut merged = list_of(items.clone());
+        merged.sort_by_key(|p| p.0);
+        assert_eq!(merged.into_iter().collect::<Vec<_>>(), expected);
+
+        let mut inserted = list_of(items);
+        inserted.insertion_sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(inserted.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn insertion_sort_nearly_sorted_test() {
+        // a few elements out of place, including a new minimum at the end
+        let mut items: Vec<i32> = (1..1000).collect();
+        items.swap(10, 11);
+        items.swap(500, 505);
+        items.push(0);
+
+        let mut list = list_of(items);
+        list.insertion_sort();
+        assert_eq!(list.peek(), Some(0));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+
+        // adjacent pairs swapped, every second node goes in right behind the previous insertion point
+        let items: Vec<i32> = (0..1000).map(|i| i ^ 1).collect();
+        let mut comparisons = 0;
+        let mut list = list_of(items);
+        list.insertion_sort_by(|a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+        assert!(comparisons <= 3 * 1000, "{} comparisons", comparisons);
+
+        let mut list = list_of(["pear", "fig", "banana", "kiwi"]);
+        list.insertion_sort_by_key(|w| w.len());
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec!["fig", "pear", "kiwi", "banana"]);
+    }
+
     
 }
\ No newline at end of file
@@ -0,0 +1,577 @@
+/***
+ * SmallArrayList
+ * ArrayList that keeps up to N elements inline and only moves to the heap past that
+ * append/pop/get has O(1)
+ * prepend has O(n)
+ *
+ * Starts out as an ArrayVecList<T, N>. The first insert that doesn't fit spills every element
+ * into an ArrayList, which grows by doubling from there on. It stays on the heap until
+ * shrink_to_fit finds that the elements fit inline again, spilled() tells which one it is.
+ *
+ * Same API as ArrayList (and ArrayVecList, minus the capacity errors), swapping one for
+ * another is a change of type. Derefs to [T] like both of them. The growth policy applies
+ * from the spill on, memory_stats describes the heap buffer once spilled and the N inline
+ * slots (never reallocated) before that.
+ *
+ * Upsides:
+ * - Short lists cost no allocation at all, and live next to whatever holds them
+ * - No limit on the length, unlike ArrayVecList
+ * - Same cache friendly contiguous layout either way
+ *
+ * Downsides:
+ * - Every access checks where the elements are first
+ * - The inline slots are paid for even after spilling, a big N makes the list big
+ * - Spilling moves every element once
+ */
+
+use std::fmt;
+use std::mem;
+use std::ops::RangeBounds;
+use super::array_list::{
+    ArrayList, ArrayListDrain, ArrayListError, ArrayListIntoIter, ArrayListIterMut, ArrayListIterator, GrowthPolicy,
+    MemoryStats,
+};
+use super::array_vec_list::{ArrayVecList, ArrayVecListIntoIter};
+use super::raw_list::{check_insert_index, range_of, slice_list_impls, RawList};
+
+pub struct SmallArrayList<T, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+enum Storage<T, const N: usize> {
+    Inline(ArrayVecList<T, N>, GrowthPolicy), // the policy the heap list gets on spilling
+    Heap(ArrayList<T>),
+}
+
+pub struct SmallArrayListIntoIter<T, const N: usize> {
+    inner: IntoIterStorage<T, N>,
+}
+
+enum IntoIterStorage<T, const N: usize> {
+    Inline(ArrayVecListIntoIter<T, N>),
+    Heap(ArrayListIntoIter<T>),
+}
+
+impl<T, const N: usize> SmallArrayList<T, N> {
+    pub const fn new() -> Self {
+        SmallArrayList { storage: Storage::Inline(ArrayVecList::new(), GrowthPolicy::Doubling) }
+    }
+
+    // inline if capacity fits in N, otherwise on the heap right away
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_growth_policy(capacity, GrowthPolicy::default())
+    }
+
+    pub fn with_growth_policy(capacity: usize, growth: GrowthPolicy) -> Self {
+        let storage = match capacity <= N {
+            true => Storage::Inline(ArrayVecList::new(), growth),
+            false => Storage::Heap(ArrayList::with_growth_policy(capacity, growth)),
+        };
+        SmallArrayList { storage }
+    }
+
+    pub fn growth_policy(&self) -> &GrowthPolicy {
+        match &self.storage {
+            Storage::Inline(_, growth) => growth,
+            Storage::Heap(list) => list.growth_policy(),
+        }
+    }
+
+    // applies from the next growth on, which for an inline list is the spill
+    pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+        match &mut self.storage {
+            Storage::Inline(_, current) => *current = growth,
+            Storage::Heap(list) => list.set_growth_policy(growth),
+        }
+    }
+
+    // the heap buffer's stats once spilled, the inline slots before that
+    pub fn memory_stats(&self) -> MemoryStats {
+        match &self.storage {
+            Storage::Inline(..) => {
+                let bytes = N.saturating_mul(mem::size_of::<T>());
+                MemoryStats { capacity: N, peak_capacity: N, bytes, peak_bytes: bytes, ..MemoryStats::default() }
+            }
+            Storage::Heap(list) => list.memory_stats(),
+        }
+    }
+
+    // true once the elements live on the heap
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    // N while inline
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(list, _) => list.capacity(),
+            Storage::Heap(list) => list.capacity(),
+        }
+    }
+
+    // room for at least additional more elements, spills if the inline slots are too few
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.storage {
+            Storage::Inline(list, growth) if additional > list.remaining_capacity() => {
+                let next = growth.next_capacity(N);
+                let needed = self.needed(additional);
+                self.spill(next.max(needed));
+            }
+            Storage::Inline(..) => {}
+            Storage::Heap(list) => list.reserve(additional),
+        }
+    }
+
+    // room for exactly additional more elements
+    pub fn reserve_exact(&mut self, additional: usize) {
+        match &mut self.storage {
+            Storage::Inline(list, _) if additional > list.remaining_capacity() => {
+                let needed = self.needed(additional);
+                self.spill(needed);
+            }
+            Storage::Inline(..) => {}
+            Storage::Heap(list) => list.reserve_exact(additional),
+        }
+    }
+
+    // moves the elements back inline if they fit, otherwise shrinks the heap buffer to len
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    // like ArrayList::shrink_to, a target that fits in N moves the elements back inline
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        if let Storage::Heap(list) = &mut self.storage {
+            if min_capacity.max(list.len()) > N {
+                list.shrink_to(min_capacity);
+                return;
+            }
+            let mut inline = ArrayVecList::new();
+            inline.raw().insert_from(0, list.raw());
+            self.storage = Storage::Inline(inline, list.growth_policy().clone());
+        }
+    }
+
+    // length + additional
+    fn needed(&self, additional: usize) -> usize {
+        self.len().checked_add(additional).expect("SmallArrayList capacity overflow")
+    }
+
+    // moves the inline elements into a heap buffer of capacity, capacity >= len
+    fn spill(&mut self, capacity: usize) {
+        if let Storage::Inline(list, growth) = &mut self.storage {
+            let mut heap = ArrayList::with_growth_policy(capacity, mem::take(growth));
+            heap.raw().insert_from(0, list.raw());
+            self.storage = Storage::Heap(heap);
+        }
+    }
+
+    pub fn append(&mut self, item: T) {
+        self.reserve(1);
+        self.raw().push(item);
+    }
+
+    pub fn prepend(&mut self, item: T) {
+        self.reserve(1);
+        self.raw().insert(0, item);
+    }
+
+    // insert el at index, everything from index on moves one to the right. index == len appends
+    pub fn insert_at(&mut self, index: usize, el: T) -> Result<(), ArrayListError> {
+        check_insert_index(index, self.len())?;
+        self.reserve(1);
+        self.raw().insert(index, el);
+        Ok(())
+    }
+
+    // clones items in front of index, index == len appends them
+    pub fn insert_slice(&mut self, index: usize, items: &[T]) -> Result<(), ArrayListError>
+    where
+        T: Clone,
+    {
+        check_insert_index(index, self.len())?;
+        // cloned up front, a panicking clone leaves self untouched
+        self.insert_list(index, items.iter().cloned().collect());
+        Ok(())
+    }
+
+    pub fn extend_from_slice(&mut self, items: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(items.len());
+        for item in items {
+            self.append(item.clone());
+        }
+    }
+
+    // drops the elements in range and closes the gap
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) -> Result<(), ArrayListError> {
+        self.drain(range)?;
+        Ok(())
+    }
+
+    // removes range and yields its elements, see ArrayList::drain. Never moves the list back inline
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Result<ArrayListDrain<'_, T>, ArrayListError> {
+        let (start, end) = range_of(range, self.len())?;
+        Ok(ArrayListDrain::new(self.raw(), start, end))
+    }
+
+    // replaces range with replace_with and returns the removed elements
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Result<SmallArrayList<T, N>, ArrayListError>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let (start, end) = range_of(range, self.len())?;
+        let removed = self.drain(start..end)?.collect();
+        self.insert_list(start, replace_with.into_iter().collect());
+        Ok(removed)
+    }
+
+    // keeps the elements keep returns true for, in order
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, keep: F) {
+        self.raw().retain(keep);
+    }
+
+    // same(a, b) is called with a later element and the last kept one, true drops a
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, same: F) {
+        self.raw().dedup_by(same);
+    }
+
+    // drops everything from len on, does nothing if the list is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        self.raw().truncate(len);
+    }
+
+    // moves the elements from at on into a new list, inline if they fit
+    pub fn split_off(&mut self, at: usize) -> Result<SmallArrayList<T, N>, ArrayListError> {
+        check_insert_index(at, self.len())?;
+        let mut other = SmallArrayList::with_capacity(self.len() - at);
+        self.raw().split_into(at, other.raw());
+        Ok(other)
+    }
+
+    // removes the element at index and moves the last one into its place, O(1), doesn't keep order
+    pub fn swap_remove(&mut self, index: usize) -> Result<T, ArrayListError> {
+        let len = self.len();
+        self.raw().swap_remove(index).ok_or(ArrayListError::IndexOutOfBounds { index, len })
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.raw().pop()
+    }
+
+    pub fn pop_at(&mut self, index: usize) -> Option<T> {
+        self.raw().remove(index)
+    }
+
+    // the live elements
+    pub fn as_slice(&self) -> &[T] {
+        match &self.storage {
+            Storage::Inline(list, _) => list.as_slice(),
+            Storage::Heap(list) => list.as_slice(),
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.storage {
+            Storage::Inline(list, _) => list.as_mut_slice(),
+            Storage::Heap(list) => list.as_mut_slice(),
+        }
+    }
+
+    // moves all of items in front of index, items ends up empty
+    fn insert_list(&mut self, index: usize, mut items: SmallArrayList<T, N>) {
+        self.reserve(items.len());
+        self.raw().insert_from(index, items.raw());
+    }
+
+    fn raw(&mut self) -> RawList<'_, T> {
+        match &mut self.storage {
+            Storage::Inline(list, _) => list.raw(),
+            Storage::Heap(list) => list.raw(),
+        }
+    }
+}
+
+slice_list_impls!(SmallArrayList);
+
+impl<T, const N: usize> Iterator for SmallArrayListIntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match &mut self.inner {
+            IntoIterStorage::Inline(iter) => iter.next(),
+            IntoIterStorage::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntoIterStorage::Inline(iter) => iter.size_hint(),
+            IntoIterStorage::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for SmallArrayListIntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        match &mut self.inner {
+            IntoIterStorage::Inline(iter) => iter.next_back(),
+            IntoIterStorage::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for SmallArrayListIntoIter<T, N> {}
+
+impl<T, const N: usize> IntoIterator for SmallArrayList<T, N> {
+    type Item = T;
+    type IntoIter = SmallArrayListIntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let inner = match self.storage {
+            Storage::Inline(list, _) => IntoIterStorage::Inline(list.into_iter()),
+            Storage::Heap(list) => IntoIterStorage::Heap(list.into_iter()),
+        };
+        SmallArrayListIntoIter { inner }
+    }
+}
+
+// inline if the vec fits, otherwise takes over its buffer
+impl<T, const N: usize> From<Vec<T>> for SmallArrayList<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        match vec.len() <= N {
+            true => vec.into_iter().collect(),
+            false => SmallArrayList { storage: Storage::Heap(ArrayList::from(vec)) },
+        }
+    }
+}
+
+impl<T, const N: usize> From<ArrayVecList<T, N>> for SmallArrayList<T, N> {
+    fn from(list: ArrayVecList<T, N>) -> Self {
+        SmallArrayList { storage: Storage::Inline(list, GrowthPolicy::default()) }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallArrayList<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = SmallArrayList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallArrayList<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.append(item);
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallArrayList<T, N> {
+    fn clone(&self) -> Self {
+        let mut list = SmallArrayList::with_growth_policy(self.len(), self.growth_policy().clone());
+        for item in self.iter() {
+            list.append(item.clone());
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallArrayList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmallArrayList")
+            .field("length", &self.len())
+            .field("capacity", &self.capacity())
+            .field("spilled", &self.spilled())
+            .field("inner", &self.as_slice())
+            .finish()
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::lists::test_helpers::DropCounter;
+    use std::cell::Cell;
+
+    #[test]
+    fn spills_past_n() {
+        let mut list = SmallArrayList::<i32, 4>::new();
+        for i in 0..4 {
+            list.append(i);
+        }
+        assert!(!list.spilled());
+        assert_eq!(list.capacity(), 4);
+
+        list.prepend(-1);
+        assert!(list.spilled());
+        assert_eq!(list.capacity(), 8);
+        assert_eq!(list.as_slice(), &[-1, 0, 1, 2, 3]);
+
+        for i in 4..100 {
+            list.append(i);
+        }
+        assert_eq!(list.len(), 101);
+        assert_eq!(list.iter().copied().sum::<i32>(), (0..100).sum::<i32>() - 1);
+    }
+
+    #[test]
+    fn shrink_to_fit_moves_back_inline() {
+        let mut list: SmallArrayList<i32, 4> = (0..10).collect();
+        assert!(list.spilled());
+        list.truncate(6);
+        list.shrink_to_fit();
+        assert!(list.spilled());
+        assert_eq!(list.capacity(), 6);
+
+        list.truncate(3);
+        list.shrink_to_fit();
+        assert!(!list.spilled());
+        assert_eq!(list.as_slice(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn growth_policy_and_memory_stats() {
+        let mut list = SmallArrayList::<u64, 4>::with_growth_policy(0, GrowthPolicy::FixedIncrement(3));
+        list.extend(0..4);
+        let inline = list.memory_stats();
+        assert_eq!((inline.capacity, inline.bytes, inline.grows), (4, 32, 0));
+
+        // the spill grows by the policy set while inline
+        list.append(4);
+        assert_eq!(list.capacity(), 7);
+        assert!(matches!(list.growth_policy(), GrowthPolicy::FixedIncrement(3)));
+        list.set_growth_policy(GrowthPolicy::Doubling);
+        list.extend(5..8);
+        assert_eq!(list.capacity(), 14);
+        assert_eq!(list.memory_stats().grows, 1);
+
+        list.shrink_to(10);
+        assert_eq!(list.capacity(), 10);
+        list.truncate(2);
+        // a target that fits inline moves back and keeps the policy for the next spill
+        list.shrink_to(4);
+        assert!(!list.spilled());
+        assert!(matches!(list.growth_policy(), GrowthPolicy::Doubling));
+        assert_eq!(list.as_slice(), &[0, 1]);
+    }
+
+    #[test]
+    fn reserve_and_with_capacity() {
+        let mut list = SmallArrayList::<i32, 8>::with_capacity(8);
+        assert!(!list.spilled());
+        list.reserve(8);
+        assert!(!list.spilled());
+        list.reserve_exact(9);
+        assert!(list.spilled());
+        assert_eq!(list.capacity(), 9);
+        assert!(SmallArrayList::<i32, 8>::with_capacity(9).spilled());
+    }
+
+    #[test]
+    fn same_api_as_array_list() {
+        // the same edits on both lists, inline and spilled, end up the same
+        fn edit_small<const N: usize>() -> Vec<i32> {
+            let mut list: SmallArrayList<i32, N> = (0..6).collect();
+            list.insert_at(2, 10).unwrap();
+            list.insert_slice(0, &[7, 7]).unwrap();
+            list.dedup();
+            list.remove_range(1..3).unwrap();
+            let removed = list.splice(..1, [1, 2, 3]).unwrap();
+            assert_eq!(removed.as_slice(), &[7]);
+            let tail = list.split_off(5).unwrap();
+            list.extend(tail.into_iter().rev());
+            list.retain(|&x| x != 3);
+            assert_eq!(list.swap_remove(0), Ok(1));
+            list.iter().copied().collect()
+        }
+
+        let mut list: ArrayList<i32> = (0..6).collect();
+        list.insert_at(2, 10).unwrap();
+        list.insert_slice(0, &[7, 7]).unwrap();
+        list.dedup();
+        list.remove_range(1..3).unwrap();
+        let removed = list.splice(..1, [1, 2, 3]).unwrap();
+        assert_eq!(removed.as_slice(), &[7]);
+        let tail = list.split_off(5).unwrap();
+        list.extend(tail.into_iter().rev());
+        list.retain(|&x| x != 3);
+        assert_eq!(list.swap_remove(0), Ok(1));
+        let expected: Vec<i32> = list.iter().copied().collect();
+
+        assert_eq!(edit_small::<2>(), expected);
+        assert_eq!(edit_small::<16>(), expected);
+    }
+
+    #[test]
+    fn errors_match_array_list() {
+        let mut list: SmallArrayList<i32, 2> = [1, 2].into_iter().collect();
+        assert_eq!(list.insert_at(3, 0), Err(ArrayListError::IndexOutOfBounds { index: 3, len: 2 }));
+        assert_eq!(list.split_off(3).err(), Some(ArrayListError::IndexOutOfBounds { index: 3, len: 2 }));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = list.drain(2..1).err();
+        assert_eq!(reversed, Some(ArrayListError::InvalidRange { len: 2 }));
+        assert_eq!(list.pop_at(2), None);
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let drops = Cell::new(0);
+        let mut list = SmallArrayList::<DropCounter, 2>::new();
+        for _ in 0..5 {
+            list.append(DropCounter(&drops));
+        }
+        // spilling moves, it doesn't drop
+        assert_eq!(drops.get(), 0);
+        list.drain(1..3).unwrap();
+        assert_eq!(drops.get(), 2);
+
+        let mut iter = list.into_iter();
+        drop(iter.next_back());
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn conversions() {
+        let small: SmallArrayList<i32, 4> = vec![1, 2, 3].into();
+        assert!(!small.spilled());
+        let big: SmallArrayList<i32, 2> = vec![1, 2, 3].into();
+        assert!(big.spilled());
+        assert_eq!(small.as_slice(), big.as_slice());
+
+        let fixed: ArrayVecList<i32, 4> = [3, 1, 2].into_iter().collect();
+        let mut list = SmallArrayList::from(fixed);
+        list.sort();
+        assert_eq!(list, [1, 2, 3].into_iter().collect());
+        assert_eq!(list.clone(), list);
+        assert_eq!(
+            format!("{:?}", list),
+            "SmallArrayList { length: 3, capacity: 4, spilled: false, inner: [1, 2, 3] }"
+        );
+    }
+
+    #[test]
+    fn zero_sized_and_zero_inline() {
+        let mut units = SmallArrayList::<(), 2>::new();
+        for _ in 0..10 {
+            units.append(());
+        }
+        assert_eq!(units.len(), 10);
+
+        let mut list = SmallArrayList::<i32, 0>::new();
+        list.append(1);
+        assert!(list.spilled());
+        assert_eq!(list.pop(), Some(1));
+    }
+}
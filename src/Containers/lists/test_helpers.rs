@@ -0,0 +1,16 @@
+/***
+ * Test helpers
+ * Fixtures shared by the list tests, test builds only
+ */
+
+use std::cell::Cell;
+
+// counts its drops in a shared cell
+#[derive(Debug)]
+pub(super) struct DropCounter<'a>(pub(super) &'a Cell<usize>);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
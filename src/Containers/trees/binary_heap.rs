@@ -1,19 +1,456 @@
-/*
-    An Arena Allocated Binary Heap,
-    Used as both a max heap & min heap depending on provided flag
-
-*/
+/***
+ * Binary Heap
+ * An Arena Allocated Binary Heap (all nodes live in one Vec, links are implicit indices),
+ * Used as both a max heap & min heap depending on provided flag
+ * push, pop: O(log n) time complexity
+ * peek, len, is_empty: O(1) time complexity
+ * building from a Vec or iterator: O(n) via bottom-up heapify
+ *
+ * Upsides:
+ * - No per node allocation, a single contiguous buffer
+ * - Very CPU cache friendly compared to pointer based trees
+ * - Cheap O(n) construction from unordered data
+ * - Priority order (min or max) chosen at construction, no wrapper types needed
+ *
+ * Downsides:
+ * - Only the top element is accessible in O(1), searching is O(n)
+ * - Not stable, equal priorities are popped in no particular order
+ * - Priorities can't be changed in place (see IndexedBinaryHeap)
+ */
 
 
 /*
-    Structural notes for self reference:
+    Structural notes for self reference (0 indexed):
 
-    Parent = i/2  // rounded down
-    Left = 2i
-    Right = 21 + 1
+    Parent = (i - 1) / 2  // rounded down
+    Left = 2i + 1
+    Right = 2i + 2
 
 */
 
+use std::fmt;
+
+/// Decides which element ends up on top of a `BinaryHeap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapType {
+    Max, // largest element on top
+    Min, // smallest element on top
+}
+
 pub struct BinaryHeap<T> {
     heap: Vec<T>,
-}
\ No newline at end of file
+    heap_type: HeapType,
+}
+
+impl<T: PartialOrd> BinaryHeap<T> {
+    pub fn new(heap_type: HeapType) -> Self {
+        BinaryHeap {
+            heap: Vec::new(),
+            heap_type,
+        }
+    }
+
+    pub fn new_max() -> Self {
+        Self::new(HeapType::Max)
+    }
+
+    pub fn new_min() -> Self {
+        Self::new(HeapType::Min)
+    }
+
+    pub fn with_capacity(capacity: usize, heap_type: HeapType) -> Self {
+        BinaryHeap {
+            heap: Vec::with_capacity(capacity),
+            heap_type,
+        }
+    }
+
+    // takes ownership of the vec and heapifies it in place, O(n)
+    pub fn from_vec(vec: Vec<T>, heap_type: HeapType) -> Self {
+        let mut heap = BinaryHeap { heap: vec, heap_type };
+        heap.rebuild();
+        heap
+    }
+
+    pub fn from_iter_with<I: IntoIterator<Item = T>>(iter: I, heap_type: HeapType) -> Self {
+        Self::from_vec(iter.into_iter().collect(), heap_type)
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.heap.push(item);
+        let last = self.heap.len() - 1;
+        self.sift_up(last);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        // move the last leaf to the root and let it sink back down
+        let mut item = self.heap.pop()?;
+        if !self.heap.is_empty() {
+            std::mem::swap(&mut item, &mut self.heap[0]);
+            self.sift_down(0);
+        }
+        Some(item)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.first()
+    }
+
+    // pushes item and pops the top in one sift, cheaper than push followed by pop
+    pub fn push_pop(&mut self, mut item: T) -> T {
+        if let Some(top) = self.heap.first() {
+            if self.has_priority(top, &item) {
+                std::mem::swap(&mut item, &mut self.heap[0]);
+                self.sift_down(0);
+            }
+        }
+        item
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.heap.capacity()
+    }
+
+    pub fn heap_type(&self) -> HeapType {
+        self.heap_type
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    // iterates the arena in storage order, not priority order
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.heap.iter()
+    }
+
+    // hands back the arena in storage order, not priority order
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap
+    }
+
+    // consumes the heap and returns its elements in ascending order, O(n log n)
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // repeatedly move the top behind the shrinking heap, in place like heapsort
+        let mut end = self.heap.len();
+        while end > 1 {
+            end -= 1;
+            self.heap.swap(0, end);
+            self.sift_down_range(0, end, |_, _| {});
+        }
+        // a max heap leaves the buffer ascending, a min heap leaves it descending
+        if self.heap_type == HeapType::Min {
+            self.heap.reverse();
+        }
+        self.heap
+    }
+
+    // true if a belongs closer to the top than b
+    pub(crate) fn has_priority(&self, a: &T, b: &T) -> bool {
+        match self.heap_type {
+            HeapType::Max => a > b,
+            HeapType::Min => a < b,
+        }
+    }
+
+    // bottom-up heapify of the whole arena, O(n)
+    fn rebuild(&mut self) {
+        let len = self.heap.len();
+        for i in (0..len / 2).rev() {
+            self.sift_down_range(i, len, |_, _| {});
+        }
+    }
+
+    fn sift_up(&mut self, i: usize) -> usize {
+        self.sift_up_with(i, |_, _| {})
+    }
+
+    fn sift_down(&mut self, i: usize) -> usize {
+        let len = self.heap.len();
+        self.sift_down_range(i, len, |_, _| {})
+    }
+
+    /*
+        The sifts below report every element they move through `moved(item, new_index)`.
+        Plain heap operations pass a no-op, the indexed heap uses it to keep its
+        handle -> position table in sync. Both return the final index of the sifted element.
+    */
+
+    pub(crate) fn sift_up_with<F>(&mut self, mut i: usize, mut moved: F) -> usize
+    where
+        F: FnMut(&T, usize),
+    {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.has_priority(&self.heap[i], &self.heap[parent]) {
+                break;
+            }
+            self.heap.swap(i, parent);
+            moved(&self.heap[i], i);
+            i = parent;
+        }
+        if i < self.heap.len() {
+            moved(&self.heap[i], i);
+        }
+        i
+    }
+
+    pub(crate) fn sift_down_range<F>(&mut self, mut i: usize, end: usize, mut moved: F) -> usize
+    where
+        F: FnMut(&T, usize),
+    {
+        loop {
+            let left = 2 * i + 1;
+            let right = left + 1;
+            let mut top = i;
+
+            if left < end && self.has_priority(&self.heap[left], &self.heap[top]) {
+                top = left;
+            }
+            if right < end && self.has_priority(&self.heap[right], &self.heap[top]) {
+                top = right;
+            }
+            if top == i {
+                break;
+            }
+            self.heap.swap(i, top);
+            moved(&self.heap[i], i);
+            i = top;
+        }
+        if i < end {
+            moved(&self.heap[i], i);
+        }
+        i
+    }
+
+    pub(crate) fn sift_down_with<F>(&mut self, i: usize, moved: F) -> usize
+    where
+        F: FnMut(&T, usize),
+    {
+        let len = self.heap.len();
+        self.sift_down_range(i, len, moved)
+    }
+
+    // raw arena access for wrappers that track positions themselves,
+    // callers are responsible for restoring heap order afterwards
+    pub(crate) fn arena(&self) -> &[T] {
+        &self.heap
+    }
+
+    pub(crate) fn arena_mut(&mut self) -> &mut [T] {
+        &mut self.heap
+    }
+
+    pub(crate) fn push_unsifted(&mut self, item: T) -> usize {
+        self.heap.push(item);
+        self.heap.len() - 1
+    }
+
+    pub(crate) fn swap_remove_unsifted(&mut self, i: usize) -> T {
+        self.heap.swap_remove(i)
+    }
+}
+
+// default collection behaviour is a max heap, like std
+impl<T: PartialOrd> FromIterator<T> for BinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_iter_with(iter, HeapType::Max)
+    }
+}
+
+impl<T: PartialOrd> From<Vec<T>> for BinaryHeap<T> {
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_vec(vec, HeapType::Max)
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for BinaryHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let old_len = self.heap.len();
+        self.heap.extend(iter);
+        let added = self.heap.len() - old_len;
+        // rebuilding is O(n), sifting each new item up is O(k log n); pick the cheaper one
+        if added > old_len {
+            self.rebuild();
+        } else {
+            for i in old_len..self.heap.len() {
+                self.sift_up(i);
+            }
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new_max()
+    }
+}
+
+impl<T: Clone> Clone for BinaryHeap<T> {
+    fn clone(&self) -> Self {
+        BinaryHeap {
+            heap: self.heap.clone(),
+            heap_type: self.heap_type,
+        }
+    }
+}
+
+// Draining iterator, yields elements in priority order
+pub struct BinaryHeapIntoIter<T> {
+    heap: BinaryHeap<T>,
+}
+
+impl<T: PartialOrd> Iterator for BinaryHeapIntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: PartialOrd> IntoIterator for BinaryHeap<T> {
+    type Item = T;
+    type IntoIter = BinaryHeapIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        BinaryHeapIntoIter { heap: self }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BinaryHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BinaryHeap")
+            .field("heap_type", &self.heap_type)
+            .field("len", &self.heap.len())
+            .field("heap", &self.heap)
+            .finish()
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    // checks the heap property for every parent/child pair
+    fn assert_heap_property<T: PartialOrd>(heap: &BinaryHeap<T>) {
+        let slice = &heap.heap;
+        for i in 1..slice.len() {
+            let parent = (i - 1) / 2;
+            assert!(!heap.has_priority(&slice[i], &slice[parent]));
+        }
+    }
+
+    #[test]
+    fn max_heap_push_pop() {
+        let mut heap = BinaryHeap::new_max();
+        heap.push(3);
+        heap.push(1);
+        heap.push(4);
+        heap.push(1);
+        heap.push(5);
+
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.peek(), Some(&5));
+        assert_eq!(heap.pop(), Some(5));
+        assert_eq!(heap.pop(), Some(4));
+        assert_eq!(heap.pop(), Some(3));
+        assert_eq!(heap.pop(), Some(1));
+        assert_eq!(heap.pop(), Some(1));
+        assert_eq!(heap.pop(), None);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn min_heap_push_pop() {
+        let mut heap = BinaryHeap::new_min();
+        for i in [7, 2, 9, 4, 0] {
+            heap.push(i);
+        }
+
+        assert_eq!(heap.peek(), Some(&0));
+        let popped: Vec<_> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(popped, vec![0, 2, 4, 7, 9]);
+        assert_eq!(heap.peek(), None);
+    }
+
+    #[test]
+    fn from_vec_heapifies() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+
+        let max = BinaryHeap::from_vec(arr.clone(), HeapType::Max);
+        assert_heap_property(&max);
+        assert_eq!(max.peek(), Some(&299));
+
+        let min = BinaryHeap::from_vec(arr, HeapType::Min);
+        assert_heap_property(&min);
+        assert_eq!(min.peek(), Some(&0));
+    }
+
+    #[test]
+    fn into_sorted_vec_is_ascending() {
+        let mut arr: Vec<_> = (0..300).collect();
+        arr.shuffle(&mut thread_rng());
+
+        let max: BinaryHeap<_> = arr.iter().copied().collect();
+        assert_eq!(max.into_sorted_vec(), (0..300).collect::<Vec<_>>());
+
+        let min = BinaryHeap::from_iter_with(arr, HeapType::Min);
+        assert_eq!(min.into_sorted_vec(), (0..300).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn push_pop_and_extend() {
+        let mut heap = BinaryHeap::new_min();
+        assert_eq!(heap.push_pop(5), 5); // empty heap hands the item straight back
+        heap.extend(vec![4, 8, 6]);
+        assert_eq!(heap.push_pop(7), 4);
+        assert_eq!(heap.push_pop(1), 1);
+        assert_heap_property(&heap);
+
+        // large extend triggers a rebuild instead of per item sifting
+        heap.extend(0..100);
+        assert_heap_property(&heap);
+        assert_eq!(heap.len(), 103);
+        assert_eq!(heap.peek(), Some(&0));
+    }
+
+    #[test]
+    fn into_iter_yields_priority_order() {
+        let heap = BinaryHeap::from_vec(vec![2.5, -1.0, 10.0, 3.25], HeapType::Max);
+        let drained: Vec<f64> = heap.into_iter().collect();
+        assert_eq!(drained, vec![10.0, 3.25, 2.5, -1.0]);
+    }
+
+    // test 10000 elements
+    #[cfg(feature = "heavy_test")]
+    #[test]
+    fn heap_10000() {
+        let mut arr: Vec<_> = (0..10000).collect();
+        arr.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::new_min();
+        for i in arr {
+            heap.push(i);
+        }
+        for i in 0..10000 {
+            assert_eq!(heap.pop(), Some(i));
+        }
+    }
+}
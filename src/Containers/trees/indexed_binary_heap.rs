@@ -0,0 +1,406 @@
+/***
+ * Indexed Binary Heap
+ * A BinaryHeap that hands back a stable Handle for every pushed element,
+ * so priorities can be changed or elements removed after the fact.
+ * push, pop, decrease_key, increase_key, update, remove: O(log n) time complexity
+ * peek, get, contains: O(1) time complexity
+ *
+ * Upsides:
+ * - Priorities can be updated in place (Dijkstra, Prim, event simulation)
+ * - Same arena layout and min/max flag as BinaryHeap
+ * - Handles are generation checked, a stale handle is rejected instead of hitting another element
+ *
+ * Downsides:
+ * - Extra handle -> position table, every sift also updates it
+ * - Handles are only meaningful for the heap that created them
+ */
+
+use std::fmt;
+use super::binary_heap::{BinaryHeap, HeapType};
+
+/// Stable reference to an element pushed onto an `IndexedBinaryHeap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexedHeapError {
+    InvalidHandle,  // handle was removed, popped or belongs to another heap
+    WrongDirection, // decrease_key got a bigger key or increase_key a smaller one
+}
+
+impl fmt::Display for IndexedHeapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexedHeapError::InvalidHandle => write!(f, "handle does not refer to an element in this heap"),
+            IndexedHeapError::WrongDirection => write!(f, "new key moves in the wrong direction for this operation"),
+        }
+    }
+}
+
+impl std::error::Error for IndexedHeapError {}
+
+// arena entry, ordered by priority only so the slot never takes part in comparisons
+struct Entry<T> {
+    priority: T,
+    slot: usize,
+}
+
+impl<T: PartialEq> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.priority.partial_cmp(&other.priority)
+    }
+}
+
+// position of a handle in the arena, generation is bumped every time the slot is freed
+struct Slot {
+    position: Option<usize>,
+    generation: u32,
+}
+
+pub struct IndexedBinaryHeap<T> {
+    heap: BinaryHeap<Entry<T>>,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+}
+
+impl<T: PartialOrd> IndexedBinaryHeap<T> {
+    pub fn new(heap_type: HeapType) -> Self {
+        IndexedBinaryHeap {
+            heap: BinaryHeap::new(heap_type),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+        }
+    }
+
+    pub fn new_max() -> Self {
+        Self::new(HeapType::Max)
+    }
+
+    pub fn new_min() -> Self {
+        Self::new(HeapType::Min)
+    }
+
+    pub fn with_capacity(capacity: usize, heap_type: HeapType) -> Self {
+        IndexedBinaryHeap {
+            heap: BinaryHeap::with_capacity(capacity, heap_type),
+            slots: Vec::with_capacity(capacity),
+            free_slots: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, priority: T) -> Handle {
+        // reuse a freed slot if there is one, its generation already moved on
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot { position: None, generation: 0 });
+                self.slots.len() - 1
+            }
+        };
+        let index = self.heap.push_unsifted(Entry { priority, slot });
+        let slots = &mut self.slots;
+        self.heap.sift_up_with(index, |entry, i| slots[entry.slot].position = Some(i));
+
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<(Handle, T)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let entry = self.remove_at(0);
+        let handle = self.release(entry.slot);
+        Some((handle, entry.priority))
+    }
+
+    pub fn peek(&self) -> Option<(Handle, &T)> {
+        self.heap.peek().map(|entry| {
+            (self.handle_of(entry.slot), &entry.priority)
+        })
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|i| &self.heap.arena()[i].priority)
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    // lowers the key of handle, new_priority must not be greater than the current one
+    pub fn decrease_key(&mut self, handle: Handle, new_priority: T) -> Result<(), IndexedHeapError> {
+        let i = self.position(handle).ok_or(IndexedHeapError::InvalidHandle)?;
+        if new_priority > self.heap.arena()[i].priority {
+            return Err(IndexedHeapError::WrongDirection);
+        }
+        self.replace_at(i, new_priority);
+        Ok(())
+    }
+
+    // raises the key of handle, new_priority must not be smaller than the current one
+    pub fn increase_key(&mut self, handle: Handle, new_priority: T) -> Result<(), IndexedHeapError> {
+        let i = self.position(handle).ok_or(IndexedHeapError::InvalidHandle)?;
+        if new_priority < self.heap.arena()[i].priority {
+            return Err(IndexedHeapError::WrongDirection);
+        }
+        self.replace_at(i, new_priority);
+        Ok(())
+    }
+
+    // changes the key in either direction and returns the old one
+    pub fn update(&mut self, handle: Handle, new_priority: T) -> Result<T, IndexedHeapError> {
+        let i = self.position(handle).ok_or(IndexedHeapError::InvalidHandle)?;
+        Ok(self.replace_at(i, new_priority))
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let i = self.position(handle)?;
+        let entry = self.remove_at(i);
+        self.release(entry.slot);
+        Some(entry.priority)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn heap_type(&self) -> HeapType {
+        self.heap.heap_type()
+    }
+
+    // invalidates every outstanding handle
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    fn handle_of(&self, slot: usize) -> Handle {
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    // frees slot for reuse and returns the handle it had while alive
+    fn release(&mut self, slot: usize) -> Handle {
+        let handle = self.handle_of(slot);
+        self.slots[slot].position = None;
+        self.slots[slot].generation = self.slots[slot].generation.wrapping_add(1);
+        self.free_slots.push(slot);
+        handle
+    }
+
+    fn replace_at(&mut self, i: usize, new_priority: T) -> T {
+        let old = std::mem::replace(&mut self.heap.arena_mut()[i].priority, new_priority);
+        self.restore(i);
+        old
+    }
+
+    fn remove_at(&mut self, i: usize) -> Entry<T> {
+        // the last leaf fills the hole and has to be sifted into place
+        let removed = self.heap.swap_remove_unsifted(i);
+        if i < self.heap.len() {
+            self.restore(i);
+        }
+        removed
+    }
+
+    // an element changed at i, only one of the two sifts will actually move it
+    fn restore(&mut self, i: usize) {
+        let slots = &mut self.slots;
+        let i = self.heap.sift_up_with(i, |entry, at| slots[entry.slot].position = Some(at));
+        self.heap.sift_down_with(i, |entry, at| slots[entry.slot].position = Some(at));
+    }
+}
+
+impl<T: PartialOrd> Default for IndexedBinaryHeap<T> {
+    fn default() -> Self {
+        Self::new_max()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IndexedBinaryHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexedBinaryHeap")
+            .field("len", &self.slots.iter().filter(|slot| slot.position.is_some()).count())
+            .field("slots", &self.slots.len())
+            .finish()
+    }
+}
+
+
+
+// **********  TESTS ********** //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    // every live handle must point at the arena index that holds its slot
+    fn assert_positions<T: PartialOrd>(heap: &IndexedBinaryHeap<T>) {
+        for (i, entry) in heap.heap.arena().iter().enumerate() {
+            assert_eq!(heap.slots[entry.slot].position, Some(i));
+        }
+        for i in 1..heap.len() {
+            let arena = heap.heap.arena();
+            assert!(!heap.heap.has_priority(&arena[i], &arena[(i - 1) / 2]));
+        }
+    }
+
+    #[test]
+    fn push_pop_min() {
+        let mut heap = IndexedBinaryHeap::new_min();
+        let a = heap.push(5);
+        let b = heap.push(1);
+        let c = heap.push(3);
+        assert_positions(&heap);
+
+        assert_eq!(heap.peek(), Some((b, &1)));
+        assert_eq!(heap.pop(), Some((b, 1)));
+        assert_eq!(heap.pop(), Some((c, 3)));
+        assert_eq!(heap.pop(), Some((a, 5)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn decrease_and_increase_key() {
+        let mut heap = IndexedBinaryHeap::new_min();
+        let handles: Vec<Handle> = (0..10).map(|i| heap.push(i * 10)).collect();
+
+        heap.decrease_key(handles[7], 5).unwrap();
+        assert_positions(&heap);
+        assert_eq!(heap.get(handles[7]), Some(&5));
+        assert_eq!(heap.peek(), Some((handles[0], &0)));
+
+        heap.increase_key(handles[0], 95).unwrap();
+        assert_positions(&heap);
+        assert_eq!(heap.peek(), Some((handles[7], &5)));
+
+        // wrong direction leaves the heap untouched
+        assert_eq!(heap.decrease_key(handles[1], 11), Err(IndexedHeapError::WrongDirection));
+        assert_eq!(heap.increase_key(handles[1], 9), Err(IndexedHeapError::WrongDirection));
+        assert_eq!(heap.get(handles[1]), Some(&10));
+
+        assert_eq!(heap.update(handles[9], -1), Ok(90));
+        assert_eq!(heap.pop(), Some((handles[9], -1)));
+    }
+
+    #[test]
+    fn max_heap_keys() {
+        let mut heap = IndexedBinaryHeap::new_max();
+        let a = heap.push(1.5);
+        let b = heap.push(2.5);
+        assert_eq!(heap.peek(), Some((b, &2.5)));
+
+        // increasing a key in a max heap moves it towards the top
+        heap.increase_key(a, 4.0).unwrap();
+        assert_eq!(heap.peek(), Some((a, &4.0)));
+        heap.decrease_key(a, 0.0).unwrap();
+        assert_eq!(heap.pop(), Some((b, 2.5)));
+    }
+
+    #[test]
+    fn remove_and_stale_handles() {
+        let mut heap = IndexedBinaryHeap::new_min();
+        let handles: Vec<Handle> = (0..20).map(|i| heap.push(i)).collect();
+
+        assert_eq!(heap.remove(handles[4]), Some(4));
+        assert_eq!(heap.remove(handles[0]), Some(0));
+        assert_eq!(heap.remove(handles[19]), Some(19));
+        assert_positions(&heap);
+        assert_eq!(heap.len(), 17);
+
+        // removed handles are dead even after their slot gets reused
+        assert_eq!(heap.remove(handles[4]), None);
+        let reused = heap.push(100);
+        assert!(!heap.contains(handles[19]));
+        assert!(heap.contains(reused));
+        assert_eq!(heap.update(handles[19], 1), Err(IndexedHeapError::InvalidHandle));
+        assert_eq!(heap.get(reused), Some(&100));
+
+        let mut popped = Vec::new();
+        while let Some((_, p)) = heap.pop() {
+            popped.push(p);
+        }
+        let mut expected: Vec<_> = (1..19).filter(|&i| i != 4).collect();
+        expected.push(100);
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn dijkstra_small_graph() {
+        // adjacency list: (to, weight)
+        let graph: Vec<Vec<(usize, u32)>> = vec![
+            vec![(1, 4), (2, 1)],
+            vec![(3, 1)],
+            vec![(1, 2), (3, 5)],
+            vec![(4, 3)],
+            vec![],
+        ];
+        let mut dist = vec![u32::MAX; graph.len()];
+        let mut handles = vec![None; graph.len()];
+        let mut heap = IndexedBinaryHeap::new_min();
+
+        dist[0] = 0;
+        handles[0] = Some(heap.push((0, 0)));
+        while let Some((_, (d, node))) = heap.pop() {
+            for &(to, w) in &graph[node] {
+                let candidate = d + w;
+                if candidate < dist[to] {
+                    dist[to] = candidate;
+                    match handles[to] {
+                        Some(h) if heap.contains(h) => heap.decrease_key(h, (candidate, to)).unwrap(),
+                        _ => handles[to] = Some(heap.push((candidate, to))),
+                    }
+                }
+            }
+        }
+        assert_eq!(dist, vec![0, 3, 1, 4, 7]);
+    }
+
+    #[test]
+    fn random_updates_keep_order() {
+        let mut rng = thread_rng();
+        let mut heap = IndexedBinaryHeap::new_min();
+        let mut values: Vec<i64> = (0..300).collect();
+        values.shuffle(&mut rng);
+        let handles: Vec<Handle> = values.iter().map(|&v| heap.push(v)).collect();
+
+        for (i, h) in handles.iter().enumerate() {
+            if i % 3 == 0 {
+                heap.update(*h, -(i as i64)).unwrap();
+            }
+        }
+        assert_positions(&heap);
+
+        let mut last = i64::MIN;
+        while let Some((_, p)) = heap.pop() {
+            assert!(last <= p);
+            last = p;
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod binary_search_tree;
 pub mod red_black_tree;
 pub mod binary_heap;
+pub mod indexed_binary_heap;
 
@@ -13,7 +13,13 @@ pub mod algorithms {
 pub use containers::{
     lists::{
         self, 
-        array_list::{ArrayList, ArrayListIterator}, 
+        array_deque::{ArrayDeque, ArrayDequeFull, ArrayDequeIntoIter, ArrayDequeIter, ArrayDequeIterMut},
+        array_list::{
+            ArrayList, ArrayListDrain, ArrayListError, ArrayListIntoIter, ArrayListIterMut, ArrayListIterator,
+            GrowthPolicy, MemoryStats
+        }, 
+        array_vec_list::{ArrayVecList, ArrayVecListError, ArrayVecListIntoIter, SpliceRejected},
+        small_array_list::{SmallArrayList, SmallArrayListIntoIter},
         doubly_linked_list::{DoublyLinkedList, DoublyLinkedListIterator}, 
         singly_linked_list::{SinglyLinkedList, SinglyLinkedListIterator}
     },
@@ -21,7 +27,8 @@ pub use containers::{
         self, 
         binary_search_tree::BinarySearchTree, 
         red_black_tree::RedBlackTree,
-        binary_heap::BinaryHeap
+        binary_heap::{BinaryHeap, HeapType},
+        indexed_binary_heap::{IndexedBinaryHeap, Handle, IndexedHeapError}
     }
 
 };
@@ -29,11 +36,47 @@ pub use containers::{
 pub use algorithms::{
     sorting::{
         self, 
-        bubble_sort::bubblesort, 
-        heap_sort::heapsort, 
-        insertion_sort::insertionsort, 
-        merge_sort::mergesort, 
-        quick_sort::quicksort, 
-        selection_sort::selectionsort
+        bubble_sort::{bubblesort, bubblesort_by, bubblesort_by_key, BubbleSort}, 
+        bucket_sort::{bucket_sort, bucket_sort_by_key},
+        cocktail_sort::{cocktailsort, cocktailsort_by, cocktailsort_by_key, CocktailSort},
+        comb_sort::{combsort, combsort_by, combsort_by_key, CombSort},
+        counting_sort::{counting_sort, counting_sort_by_key, CountingSort},
+        external_sort::{external_sort, external_sort_by, external_sort_by_key, ExternalSortConfig},
+        gnome_sort::{gnomesort, gnomesort_by, gnomesort_by_key, GnomeSort},
+        heap_sort::{heapsort, heapsort_by, heapsort_by_key, HeapSort}, 
+        inputs,
+        insertion_sort::{insertionsort, insertionsort_by, insertionsort_by_key, InsertionSort}, 
+        merge_sort::{
+            mergesort, mergesort_by, mergesort_by_key, mergesort_bottom_up, mergesort_natural,
+            MergeSort, MergeSortBottomUp, MergeSortNatural
+        }, 
+        parallel_sort::{
+            par_mergesort, par_mergesort_by, par_mergesort_by_key, par_mergesort_with, par_mergesort_with_by,
+            par_quicksort, par_quicksort_by, par_quicksort_by_key, par_quicksort_with, par_quicksort_with_by,
+            ParallelConfig, ParallelMergeSort, ParallelQuickSort
+        },
+        quick_sort::{
+            quicksort, quicksort_by, quicksort_by_key, quicksort_with, quicksort_three_way,
+            introsort, introsort_by, introsort_by_key, PivotStrategy, QuickSort, IntroSort
+        }, 
+        radix_sort::{
+            radix_sort, radix_sort_by_key, radix_sort_msd, radix_sort_msd_by_key,
+            radix_sort_bytes, radix_sort_bytes_by_key, RadixKey, RadixSort, RadixSortMsd
+        },
+        select::{
+            nth_element, nth_element_by, nth_element_by_key, quickselect, quickselect_by,
+            median_of_medians_select, median_of_medians_select_by,
+            partial_sort, partial_sort_by, partial_sort_by_key, top_k, top_k_by, top_k_by_key
+        },
+        selection_sort::{selectionsort, selectionsort_by, selectionsort_by_key, SelectionSort},
+        shell_sort::{
+            shellsort, shellsort_by, shellsort_by_key, shellsort_with, shellsort_with_by,
+            GapSequence, ShellSort
+        },
+        sorter::{Sorter, all_sorters, all_integer_sorters},
+        sorting_network::{network_sort, network_sort_by, network_sort_by_key, sorting_network, MAX_NETWORK_LEN},
+        stats::{measure, SortStats},
+        tim_sort::{timsort, timsort_by, timsort_by_key, TimSort},
+        trace::{trace, trace_events, SortEvent}
     },
 };